@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use django_auth::*;
+
+/// The iteration counts of Django 3.0, 4.0, 4.2 and a higher one.
+const ITERATIONS: [u32; 4] = [100_000, 320_000, 600_000, 1_000_000];
+
+fn bench_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("django_encode_password");
+    group.sample_size(10);
+    for iterations in ITERATIONS {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(iterations),
+            &iterations,
+            |b, &iterations| {
+                b.iter(|| django_encode_password("hello", "btQDcwXF2RoK6Q", iterations).unwrap())
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_verify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("django_auth");
+    group.sample_size(10);
+    for iterations in ITERATIONS {
+        let encoded_password =
+            django_encode_password("hello", "btQDcwXF2RoK6Q", iterations).unwrap();
+        group.bench_with_input(
+            BenchmarkId::from_parameter(iterations),
+            &encoded_password,
+            |b, encoded_password| b.iter(|| django_auth("hello", encoded_password).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode, bench_verify);
+criterion_main!(benches);
@@ -14,37 +14,92 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Encode a password in Django-style
-    Encode,
+    Encode {
+        /// Read the password from stdin, without prompting
+        #[arg(long)]
+        password_stdin: bool,
+
+        /// Salt, prompted for if missing
+        #[arg(long)]
+        salt: Option<String>,
+
+        /// Number of iterations (0 means the default), prompted for if missing
+        #[arg(long)]
+        iterations: Option<u32>,
+    },
 
     /// Verify a Django stored hashed password
-    Verify,
+    ///
+    /// e.g. `echo secret | auth verify --password-stdin --encoded 'pbkdf2_sha256$...'`
+    Verify {
+        /// Read the password from stdin, without prompting
+        #[arg(long)]
+        password_stdin: bool,
+
+        /// Read the Django stored password from stdin (after the password if
+        /// --password-stdin is given too), without prompting
+        #[arg(long, conflicts_with = "encoded")]
+        encoded_stdin: bool,
+
+        /// Django stored password, prompted for if missing
+        #[arg(long)]
+        encoded: Option<String>,
+    },
+
+    /// Verify a Django stored hashed password, and re-encode it if it uses
+    /// fewer iterations than the target
+    Upgrade {
+        /// Read the password from stdin, without prompting
+        #[arg(long)]
+        password_stdin: bool,
+
+        /// Django stored password, prompted for if missing
+        #[arg(long)]
+        encoded: Option<String>,
+
+        /// Target number of iterations
+        #[arg(long, default_value_t = DEFAULT_ITERATIONS)]
+        iterations: u32,
+    },
+
+    /// Encode a password with a random salt, like Django's make_password
+    Generate {
+        /// Generate a random password instead of prompting for one
+        #[arg(long)]
+        random_password: bool,
+    },
 }
 
 fn main() {
     let cli = Cli::parse();
 
     match &cli.command {
-        Commands::Encode => {
-            let (password, salt, iterations) = {
-                (
-                    get_user_input("Input password: "),
-                    get_user_input("Input salt: "),
-                    get_user_input_number("Input number of iterations: "),
-                )
-            };
+        Commands::Encode {
+            password_stdin,
+            salt,
+            iterations,
+        } => {
+            let password = get_input("Input password: ", *password_stdin);
+            let salt = salt
+                .clone()
+                .unwrap_or_else(|| get_user_input("Input salt: "));
+            let iterations =
+                iterations.unwrap_or_else(|| get_user_input_number("Input number of iterations: "));
 
             println!(
                 "✅ Encoded password: {}",
                 django_encode_password(&password, &salt, iterations).unwrap()
             );
         }
-        Commands::Verify => {
-            let (password, hashed_password) = {
-                (
-                    get_user_input("Input password: "),
-                    get_user_input("Input Django stored password: "),
-                )
-            };
+        Commands::Verify {
+            password_stdin,
+            encoded_stdin,
+            encoded,
+        } => {
+            let password = get_input("Input password: ", *password_stdin);
+            let hashed_password = encoded
+                .clone()
+                .unwrap_or_else(|| get_input("Input Django stored password: ", *encoded_stdin));
 
             let res = django_auth(&password, &hashed_password);
             match res {
@@ -58,6 +113,47 @@ fn main() {
                 Err(err) => println!("💔 Verification error: {:?}", err),
             }
         }
+        Commands::Upgrade {
+            password_stdin,
+            encoded,
+            iterations,
+        } => {
+            let password = get_input("Input password: ", *password_stdin);
+            let hashed_password = encoded
+                .clone()
+                .unwrap_or_else(|| get_user_input("Input Django stored password: "));
+
+            match upgrade_if_needed(&password, &hashed_password, *iterations) {
+                Ok(Some(upgraded)) => println!("✅ Upgraded password: {upgraded}"),
+                Ok(None) => println!("👌 No upgrade needed"),
+                Err(Error::IncorrectPassword) => println!("❌ Password verification failed!"),
+                Err(err) => println!("💔 Upgrade error: {:?}", err),
+            }
+        }
+        Commands::Generate { random_password } => {
+            let password = if *random_password {
+                generate_salt_with_len(16)
+            } else {
+                get_user_input("Input password: ")
+            };
+
+            match make_password(&password) {
+                Ok(encoded_password) => {
+                    println!("🔑 Password: {password}");
+                    println!("✅ Encoded password: {encoded_password}");
+                }
+                Err(err) => println!("💔 Encoding error: {:?}", err),
+            }
+        }
+    }
+}
+
+/// Read a line from stdin, prompting for it unless `from_stdin` is set.
+fn get_input(prompt: &str, from_stdin: bool) -> String {
+    if from_stdin {
+        read_line()
+    } else {
+        get_user_input(prompt)
     }
 }
 
@@ -65,15 +161,17 @@ fn get_user_input(prompt: &str) -> String {
     print!("{prompt}");
     io::stdout().flush().expect("failed to write to stdout");
 
+    read_line()
+}
+
+fn read_line() -> String {
     let stdin = io::stdin();
-    let line = stdin
+    stdin
         .lock()
         .lines()
         .next()
         .expect("failed to read password")
-        .expect("failed to read from stdin");
-
-    line
+        .expect("failed to read from stdin")
 }
 
 fn get_user_input_number(prompt: &str) -> u32 {
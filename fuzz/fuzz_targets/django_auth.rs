@@ -0,0 +1,28 @@
+#![no_main]
+
+use django_auth::*;
+use libfuzzer_sys::fuzz_target;
+
+/// Encoded passwords carry their own cost parameters (pbkdf2 iterations,
+/// bcrypt cost...), skip the expensive ones which would only be reported as
+/// timeouts.
+fn is_cheap(encoded_password: &str) -> bool {
+    encoded_password
+        .split(|c: char| !c.is_ascii_digit())
+        .all(|n| n.parse::<u32>().map_or(true, |n| n <= 16))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(encoded_password) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // the parsers must return errors, never panic
+    let _ = identify_hasher(encoded_password);
+    let _ = encoded_password.parse::<EncodedPassword>();
+    let _ = needs_update(encoded_password, 600000);
+
+    if is_cheap(encoded_password) {
+        let _ = django_auth("hello", encoded_password);
+    }
+});
@@ -0,0 +1,51 @@
+/*
+ * C bindings of the django-auth crate, built with the `capi` feature:
+ *
+ *     cargo rustc --release --features capi --crate-type staticlib
+ *
+ * (or `--crate-type cdylib` for a shared library).
+ */
+
+#ifndef DJANGO_AUTH_H
+#define DJANGO_AUTH_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define DJANGO_AUTH_OK 0
+/* A pointer argument is null. */
+#define DJANGO_AUTH_ERR_NULL_POINTER -1
+/* The encoded password is malformed (or isn't valid UTF-8). */
+#define DJANGO_AUTH_ERR_INVALID_ENCODED_PASSWORD -2
+#define DJANGO_AUTH_ERR_UNSUPPORTED_ALGORITHM -3
+#define DJANGO_AUTH_ERR_INVALID_SALT -4
+/* The password is too long. */
+#define DJANGO_AUTH_ERR_INVALID_PASSWORD -5
+#define DJANGO_AUTH_ERR_ALGORITHM_NOT_ALLOWED -6
+#define DJANGO_AUTH_ERR_INCORRECT_PASSWORD -7
+/* The algorithm is known, but the library was built without its feature. */
+#define DJANGO_AUTH_ERR_FEATURE_NOT_ENABLED -8
+/* The iterations to encode with are above the safety ceiling. */
+#define DJANGO_AUTH_ERR_INVALID_ITERATIONS -9
+/* The requested length is out of range. */
+#define DJANGO_AUTH_ERR_INVALID_LENGTH -10
+/* An unexpected internal error. */
+#define DJANGO_AUTH_ERR_PANIC -99
+
+/*
+ * Verify the NUL-terminated `password` against the Django
+ * `encoded_password`, and store whether they match in `*out`.
+ *
+ * Return DJANGO_AUTH_OK, or a negative error code in which case `*out` is
+ * left untouched.
+ */
+int django_auth_c(const char *password, const char *encoded_password, bool *out);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* DJANGO_AUTH_H */
@@ -0,0 +1,424 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::format;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::hashers::*;
+use crate::{Error, Result};
+
+/// The password hashing algorithms known by this crate, named after the prefix
+/// of their encoded passwords.
+///
+/// Parsing an algorithm (e.g. from a config file) doesn't mean it's available,
+/// all the hashers but the pbkdf2 ones (except pbkdf2_sha1) require a crate
+/// feature, see [Algorithm::is_enabled].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let algorithm: Algorithm = "pbkdf2_sha256".parse().expect("unknown algorithm");
+/// assert_eq!(algorithm, Algorithm::Pbkdf2Sha256);
+/// assert_eq!(algorithm.to_string(), "pbkdf2_sha256");
+///
+/// assert!("pbkdf2_sha384".parse::<Algorithm>().is_err());
+/// ```
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Algorithm {
+    Pbkdf2Sha256,
+    Pbkdf2Sha1,
+    /// See [Pbkdf2Sha512Hasher].
+    Pbkdf2Sha512,
+    /// See [Pbkdf2Sha256HexHasher].
+    Pbkdf2Sha256Hex,
+    /// See [Pbkdf2Sha256B64SaltHasher].
+    Pbkdf2Sha256B64Salt,
+    /// See [crate::wrap_hash].
+    Pbkdf2Wrapped,
+    Argon2,
+    BcryptSha256,
+    Bcrypt,
+    Scrypt,
+    Sha1,
+    Md5,
+    UnsaltedSha1,
+    UnsaltedMd5,
+    Crypt,
+}
+
+impl Algorithm {
+    /// All the algorithms, the preferred one (Django's default) first.
+    pub const ALL: [Algorithm; 15] = [
+        Algorithm::Pbkdf2Sha256,
+        Algorithm::Pbkdf2Sha1,
+        Algorithm::Pbkdf2Sha512,
+        Algorithm::Pbkdf2Sha256Hex,
+        Algorithm::Pbkdf2Sha256B64Salt,
+        Algorithm::Pbkdf2Wrapped,
+        Algorithm::Argon2,
+        Algorithm::BcryptSha256,
+        Algorithm::Bcrypt,
+        Algorithm::Scrypt,
+        Algorithm::Sha1,
+        Algorithm::Md5,
+        Algorithm::UnsaltedSha1,
+        Algorithm::UnsaltedMd5,
+        Algorithm::Crypt,
+    ];
+
+    /// The algorithm name, i.e. the prefix of its encoded passwords.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Algorithm::Pbkdf2Sha256 => "pbkdf2_sha256",
+            Algorithm::Pbkdf2Sha1 => "pbkdf2_sha1",
+            Algorithm::Pbkdf2Sha512 => "pbkdf2_sha512",
+            Algorithm::Pbkdf2Sha256Hex => "pbkdf2_sha256_hex",
+            Algorithm::Pbkdf2Sha256B64Salt => "pbkdf2_sha256_b64salt",
+            Algorithm::Pbkdf2Wrapped => "pbkdf2_wrapped",
+            Algorithm::Argon2 => "argon2",
+            Algorithm::BcryptSha256 => "bcrypt_sha256",
+            Algorithm::Bcrypt => "bcrypt",
+            Algorithm::Scrypt => "scrypt",
+            Algorithm::Sha1 => "sha1",
+            Algorithm::Md5 => "md5",
+            Algorithm::UnsaltedSha1 => "unsalted_sha1",
+            Algorithm::UnsaltedMd5 => "unsalted_md5",
+            Algorithm::Crypt => "crypt",
+        }
+    }
+
+    /// Whether the encoded passwords of this algorithm carry an iteration
+    /// count, i.e. use the `<algorithm>$<iterations>$<salt>$<hash>` layout.
+    ///
+    /// Only the pbkdf2 algorithms do (but pbkdf2_wrapped, which has the
+    /// iterations of each layer), argon2, bcrypt and scrypt have other cost
+    /// parameters, and the legacy hashers none.
+    pub fn has_iterations(&self) -> bool {
+        matches!(
+            self,
+            Algorithm::Pbkdf2Sha256
+                | Algorithm::Pbkdf2Sha1
+                | Algorithm::Pbkdf2Sha512
+                | Algorithm::Pbkdf2Sha256Hex
+                | Algorithm::Pbkdf2Sha256B64Salt
+        )
+    }
+
+    /// The default of the main cost parameter of this algorithm, i.e. what the
+    /// default hasher (see [Algorithm::hasher]) encodes with:
+    ///
+    /// - pbkdf2: the number of iterations, [crate::default_iterations], which
+    ///   is Django 3.0's 180000 unless overridden (of each layer for
+    ///   pbkdf2_wrapped),
+    /// - argon2: the time cost, 2 (with 100 MiB of memory and a parallelism
+    ///   of 8), like Django 4.0 and later,
+    /// - bcrypt: the cost, i.e. log2 of the rounds, 12 like Django,
+    /// - scrypt: the work factor, 2^14 (with a block size of 8 and a
+    ///   parallelism of 1), like Django 4.0 and later.
+    ///
+    /// `None` is returned for the legacy hashers, which have no cost.
+    pub fn default_iterations(&self) -> Option<u32> {
+        match self {
+            Algorithm::Pbkdf2Sha256
+            | Algorithm::Pbkdf2Sha1
+            | Algorithm::Pbkdf2Sha512
+            | Algorithm::Pbkdf2Sha256Hex
+            | Algorithm::Pbkdf2Sha256B64Salt
+            | Algorithm::Pbkdf2Wrapped => Some(crate::default_iterations()),
+            Algorithm::Argon2 => Some(2),
+            Algorithm::BcryptSha256 | Algorithm::Bcrypt => Some(12),
+            Algorithm::Scrypt => Some(1 << 14),
+            Algorithm::Sha1
+            | Algorithm::Md5
+            | Algorithm::UnsaltedSha1
+            | Algorithm::UnsaltedMd5
+            | Algorithm::Crypt => None,
+        }
+    }
+
+    /// The crate feature which compiles this algorithm in, `None` for the
+    /// pbkdf2 algorithms (but pbkdf2_sha1) which are always available.
+    pub fn feature(&self) -> Option<&'static str> {
+        match self {
+            Algorithm::Pbkdf2Sha256
+            | Algorithm::Pbkdf2Sha512
+            | Algorithm::Pbkdf2Sha256Hex
+            | Algorithm::Pbkdf2Sha256B64Salt
+            | Algorithm::Pbkdf2Wrapped => None,
+            Algorithm::Pbkdf2Sha1 => Some("legacy-sha1"),
+            Algorithm::Argon2 => Some("argon2"),
+            Algorithm::BcryptSha256 | Algorithm::Bcrypt => Some("bcrypt"),
+            Algorithm::Scrypt => Some("scrypt"),
+            Algorithm::Sha1
+            | Algorithm::Md5
+            | Algorithm::UnsaltedSha1
+            | Algorithm::UnsaltedMd5
+            | Algorithm::Crypt => Some("insecure-hashers"),
+        }
+    }
+
+    /// Whether this algorithm is compiled in, see [Algorithm::feature].
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            Algorithm::Pbkdf2Sha256
+            | Algorithm::Pbkdf2Sha512
+            | Algorithm::Pbkdf2Sha256Hex
+            | Algorithm::Pbkdf2Sha256B64Salt
+            | Algorithm::Pbkdf2Wrapped => true,
+            Algorithm::Pbkdf2Sha1 => cfg!(feature = "legacy-sha1"),
+            Algorithm::Argon2 => cfg!(feature = "argon2"),
+            Algorithm::BcryptSha256 | Algorithm::Bcrypt => cfg!(feature = "bcrypt"),
+            Algorithm::Scrypt => cfg!(feature = "scrypt"),
+            Algorithm::Sha1
+            | Algorithm::Md5
+            | Algorithm::UnsaltedSha1
+            | Algorithm::UnsaltedMd5
+            | Algorithm::Crypt => cfg!(feature = "insecure-hashers"),
+        }
+    }
+
+    /// The [Error::FeatureNotEnabled] of this algorithm.
+    pub(crate) fn feature_not_enabled(&self) -> Error {
+        Error::FeatureNotEnabled(format!(
+            "enable the `{}` feature to support {self}",
+            self.feature().unwrap_or_default()
+        ))
+    }
+
+    /// Return the hasher of this algorithm, with its default parameters, or
+    /// [Error::FeatureNotEnabled] if it isn't compiled in.
+    pub fn hasher(&self) -> Result<Box<dyn Hasher>> {
+        let hasher: Box<dyn Hasher> = match self {
+            Algorithm::Pbkdf2Sha256 => Box::new(Pbkdf2Sha256Hasher::default()),
+            #[cfg(feature = "legacy-sha1")]
+            Algorithm::Pbkdf2Sha1 => Box::new(Pbkdf2Sha1Hasher::default()),
+            Algorithm::Pbkdf2Sha512 => Box::new(Pbkdf2Sha512Hasher::default()),
+            Algorithm::Pbkdf2Sha256Hex => Box::new(Pbkdf2Sha256HexHasher::default()),
+            Algorithm::Pbkdf2Sha256B64Salt => Box::new(Pbkdf2Sha256B64SaltHasher::default()),
+            Algorithm::Pbkdf2Wrapped => Box::new(wrapped::Pbkdf2WrappedHasher),
+            #[cfg(feature = "argon2")]
+            Algorithm::Argon2 => Box::new(Argon2Hasher::default()),
+            #[cfg(feature = "bcrypt")]
+            Algorithm::BcryptSha256 => Box::new(BcryptSha256Hasher::default()),
+            #[cfg(feature = "bcrypt")]
+            Algorithm::Bcrypt => Box::new(BcryptHasher::default()),
+            #[cfg(feature = "scrypt")]
+            Algorithm::Scrypt => Box::new(ScryptHasher::default()),
+            #[cfg(feature = "insecure-hashers")]
+            Algorithm::Sha1 => Box::new(Sha1Hasher),
+            #[cfg(feature = "insecure-hashers")]
+            Algorithm::Md5 => Box::new(Md5Hasher),
+            #[cfg(feature = "insecure-hashers")]
+            Algorithm::UnsaltedSha1 => Box::new(UnsaltedSha1Hasher),
+            #[cfg(feature = "insecure-hashers")]
+            Algorithm::UnsaltedMd5 => Box::new(UnsaltedMd5Hasher),
+            #[cfg(feature = "insecure-hashers")]
+            Algorithm::Crypt => Box::new(CryptHasher),
+            #[allow(unreachable_patterns)]
+            _ => return Err(self.feature_not_enabled()),
+        };
+        Ok(hasher)
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Algorithm::ALL
+            .into_iter()
+            .find(|algorithm| algorithm.as_str() == s)
+            .ok_or_else(|| Error::UnsupportedAlgorithm(s.to_owned()))
+    }
+}
+
+/// Serialized as the algorithm name, see [Algorithm::as_str].
+#[cfg(feature = "serde")]
+impl serde::Serialize for Algorithm {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+/// Deserialized from an algorithm name, unknown names are rejected with an
+/// error listing the valid ones.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Algorithm {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        const NAMES: [&str; Algorithm::ALL.len()] = {
+            let mut names = [""; Algorithm::ALL.len()];
+            let mut i = 0;
+            while i < names.len() {
+                names[i] = Algorithm::ALL[i].as_str();
+                i += 1;
+            }
+            names
+        };
+
+        let s = alloc::string::String::deserialize(deserializer)?;
+        s.parse()
+            .map_err(|_| serde::de::Error::unknown_variant(&s, &NAMES))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_algorithm() {
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                algorithm.to_string().parse::<Algorithm>().unwrap(),
+                algorithm
+            );
+        }
+
+        for s in [
+            "",
+            "foo",
+            "PBKDF2_SHA256",
+            "pbkdf2_sha256 ",
+            "pbkdf2_sha384",
+        ] {
+            let res = s.parse::<Algorithm>();
+            assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))), "{s:?}");
+        }
+
+        let with_iterations: Vec<Algorithm> = Algorithm::ALL
+            .into_iter()
+            .filter(Algorithm::has_iterations)
+            .collect();
+        assert_eq!(
+            with_iterations,
+            [
+                Algorithm::Pbkdf2Sha256,
+                Algorithm::Pbkdf2Sha1,
+                Algorithm::Pbkdf2Sha512,
+                Algorithm::Pbkdf2Sha256Hex,
+                Algorithm::Pbkdf2Sha256B64Salt
+            ]
+        );
+
+        for algorithm in [
+            Algorithm::Pbkdf2Sha256,
+            Algorithm::Pbkdf2Sha512,
+            Algorithm::Pbkdf2Sha256Hex,
+            Algorithm::Pbkdf2Sha256B64Salt,
+            Algorithm::Pbkdf2Wrapped,
+        ] {
+            assert_eq!(algorithm.hasher().unwrap().algorithm(), algorithm.as_str());
+        }
+        #[cfg(feature = "argon2")]
+        assert_eq!(Algorithm::Argon2.hasher().unwrap().algorithm(), "argon2");
+        #[cfg(feature = "scrypt")]
+        assert_eq!(Algorithm::Scrypt.hasher().unwrap().algorithm(), "scrypt");
+
+        #[cfg(feature = "insecure-hashers")]
+        assert_eq!(Algorithm::Md5.hasher().unwrap().algorithm(), "md5");
+        #[cfg(not(feature = "insecure-hashers"))]
+        assert!(matches!(
+            Algorithm::Md5.hasher(),
+            Err(Error::FeatureNotEnabled(_))
+        ));
+
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                algorithm.is_enabled(),
+                algorithm.hasher().is_ok(),
+                "{algorithm}"
+            );
+            assert_eq!(
+                algorithm.feature().is_none(),
+                algorithm.as_str().starts_with("pbkdf2_") && algorithm != Algorithm::Pbkdf2Sha1,
+                "{algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_default_iterations() {
+        assert_eq!(
+            Algorithm::Pbkdf2Sha256.default_iterations(),
+            Some(Pbkdf2Sha256Hasher::default().iterations)
+        );
+        #[cfg(feature = "legacy-sha1")]
+        assert_eq!(
+            Algorithm::Pbkdf2Sha1.default_iterations(),
+            Some(Pbkdf2Sha1Hasher::default().iterations)
+        );
+        #[cfg(feature = "argon2")]
+        assert_eq!(
+            Algorithm::Argon2.default_iterations(),
+            Some(Argon2Hasher::default().time_cost)
+        );
+        #[cfg(feature = "bcrypt")]
+        {
+            assert_eq!(
+                Algorithm::BcryptSha256.default_iterations(),
+                Some(BcryptSha256Hasher::default().cost)
+            );
+            assert_eq!(
+                Algorithm::Bcrypt.default_iterations(),
+                Some(BcryptHasher::default().cost)
+            );
+        }
+        #[cfg(feature = "scrypt")]
+        assert_eq!(
+            Algorithm::Scrypt.default_iterations(),
+            Some(ScryptHasher::default().work_factor)
+        );
+
+        let without: Vec<Algorithm> = Algorithm::ALL
+            .into_iter()
+            .filter(|algorithm| algorithm.default_iterations().is_none())
+            .collect();
+        assert_eq!(
+            without,
+            [
+                Algorithm::Sha1,
+                Algorithm::Md5,
+                Algorithm::UnsaltedSha1,
+                Algorithm::UnsaltedMd5,
+                Algorithm::Crypt
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_algorithm_serde() {
+        assert_eq!(
+            serde_json::to_string(&Algorithm::Argon2).unwrap(),
+            "\"argon2\""
+        );
+        for algorithm in Algorithm::ALL {
+            let json = serde_json::to_string(&algorithm).unwrap();
+            assert_eq!(serde_json::from_str::<Algorithm>(&json).unwrap(), algorithm);
+        }
+
+        let e = serde_json::from_str::<Algorithm>("\"pbkdf2_sha384\"").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "unknown variant `pbkdf2_sha384`, expected one of `pbkdf2_sha256`, `pbkdf2_sha1`, \
+             `pbkdf2_sha512`, `pbkdf2_sha256_hex`, `pbkdf2_sha256_b64salt`, `pbkdf2_wrapped`, \
+             `argon2`, `bcrypt_sha256`, `bcrypt`, `scrypt`, `sha1`, `md5`, `unsalted_sha1`, \
+             `unsalted_md5`, `crypt`"
+        );
+        assert!(serde_json::from_str::<Algorithm>("1").is_err());
+    }
+}
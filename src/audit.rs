@@ -0,0 +1,132 @@
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{identify_hasher, is_password_usable, Algorithm, Error, HasherRegistry};
+
+/// The result of [audit].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// The number of well-formed encoded passwords of each algorithm, by
+    /// algorithm name, e.g. "pbkdf2_sha256".
+    pub algorithms: BTreeMap<String, usize>,
+    /// The indices of the weak encoded passwords, i.e. those using a legacy
+    /// algorithm without cost parameter (sha1, md5, unsalted_sha1,
+    /// unsalted_md5 and crypt), or weaker parameters than the default hashers'
+    /// (e.g. fewer pbkdf2 iterations than [crate::default_iterations]).
+    pub weak: Vec<usize>,
+    /// The number of unusable passwords, see [crate::is_password_usable].
+    pub unusable: usize,
+    /// The indices of the malformed encoded passwords, or those of an
+    /// unsupported algorithm, which are not counted in
+    /// [AuditReport::algorithms].
+    pub malformed: Vec<usize>,
+}
+
+/// Scan `encoded` passwords, e.g. a whole users table, and report how many use
+/// each algorithm and which ones are weak, to plan a migration.
+///
+/// Nothing is hashed, so the scan is fast. Malformed entries are reported in
+/// [AuditReport::malformed] instead of aborting the scan. The encoded
+/// passwords of the algorithms which aren't compiled in (see
+/// [Error::FeatureNotEnabled]) are counted without being validated.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let report = audit(&[
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+///     "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+///     "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3",
+///     "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5",
+///     "pbkdf2_sha256$abc$seasalt$hash",
+/// ]);
+///
+/// assert_eq!(report.algorithms["pbkdf2_sha256"], 2);
+/// assert_eq!(report.algorithms["md5"], 1);
+/// assert_eq!(report.weak, [1, 2]);
+/// assert_eq!(report.unusable, 1);
+/// assert_eq!(report.malformed, [4]);
+/// ```
+///
+pub fn audit(encoded: &[&str]) -> AuditReport {
+    let registry = HasherRegistry::default();
+    let mut report = AuditReport::default();
+
+    for (i, encoded_password) in encoded.iter().enumerate() {
+        let encoded_password = encoded_password.trim_ascii();
+        if !is_password_usable(encoded_password) {
+            report.unusable += 1;
+            continue;
+        }
+
+        let outdated = match registry.hasher_for(encoded_password) {
+            Ok(hasher) => hasher.must_update(encoded_password),
+            // can't be validated without its hasher
+            Err(Error::FeatureNotEnabled(_)) => Ok(false),
+            Err(e) => Err(e),
+        };
+        let Ok(outdated) = outdated else {
+            report.malformed.push(i);
+            continue;
+        };
+
+        // hasher_for only fails after identifying the algorithm here
+        let algorithm = identify_hasher(encoded_password).unwrap_or_default();
+        *report.algorithms.entry(algorithm.into()).or_default() += 1;
+        let legacy = algorithm
+            .parse::<Algorithm>()
+            .is_ok_and(|algorithm| algorithm.default_iterations().is_none());
+        if legacy || outdated {
+            report.weak.push(i);
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit() {
+        let report = audit(&[
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=\n",
+            "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+            "88a434c88cca4e900f7874cd98123f43",
+            "!",
+            "",
+            "pbkdf2_sha256$1000$seasalt",
+            "foo$seasalt$hash",
+            "nodollar",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        ]);
+
+        let mut algorithms = BTreeMap::new();
+        algorithms.insert("pbkdf2_sha256".into(), 3);
+        algorithms.insert("sha1".into(), 1);
+        if cfg!(feature = "insecure-hashers") {
+            algorithms.insert("unsalted_md5".into(), 1);
+        }
+        assert_eq!(report.algorithms, algorithms);
+
+        if cfg!(feature = "insecure-hashers") {
+            assert_eq!(report.weak, [1, 2, 3]);
+            assert_eq!(report.malformed, [5, 6, 7, 8]);
+        } else {
+            // unsalted md5 hashes have no algorithm prefix, so they can't be
+            // identified without their hasher
+            assert_eq!(report.weak, [1, 2]);
+            assert_eq!(report.malformed, [3, 5, 6, 7, 8]);
+        }
+        assert_eq!(report.unusable, 1);
+    }
+
+    #[test]
+    fn test_audit_empty() {
+        assert_eq!(audit(&[]), AuditReport::default());
+    }
+}
@@ -0,0 +1,134 @@
+use alloc::string::String;
+
+use crate::hashers::{Hasher, Pbkdf2Sha256Hasher};
+use crate::{check_encode_password, check_encode_salt, Result};
+
+/// A builder encoding passwords with pbkdf2_sha256, like
+/// [crate::django_encode_password] without the positional arguments.
+///
+/// By default the salt is random (see [crate::generate_salt]) and the
+/// iterations are [crate::default_iterations]. The password and salt are
+/// validated like [crate::django_encode_password] does.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password = Pbkdf2Sha256::new()
+///     .iterations(320_000)
+///     .salt("btQDcwXF2RoK6Q")
+///     .encode("hello")
+///     .expect("encode error");
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$320000$btQDcwXF2RoK6Q$"));
+///
+/// // random salt, default iterations
+/// # #[cfg(feature = "std")] {
+/// let encoded_password = Pbkdf2Sha256::default().encode("hello").expect("encode error");
+/// assert!(django_auth("hello", &encoded_password).expect("auth failed"));
+/// # }
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct Pbkdf2Sha256 {
+    iterations: u32,
+    salt: Option<String>,
+}
+
+impl Pbkdf2Sha256 {
+    /// Create a builder with the default parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the number of iterations, 0 means [crate::default_iterations].
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        self.iterations = iterations;
+        self
+    }
+
+    /// Set the salt instead of generating a random one, which is required
+    /// without the `std` feature.
+    pub fn salt(mut self, salt: impl Into<String>) -> Self {
+        self.salt = Some(salt.into());
+        self
+    }
+
+    /// Encode `password`.
+    pub fn encode(&self, password: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes())
+    }
+
+    /// Same as [Pbkdf2Sha256::encode] for a password which may not be valid
+    /// UTF-8.
+    pub fn encode_bytes(&self, password: &[u8]) -> Result<String> {
+        let encode = |salt: &str| {
+            check_encode_password(password)?;
+            check_encode_salt(salt)?;
+            Pbkdf2Sha256Hasher {
+                iterations: self.iterations,
+            }
+            .encode_bytes(password, salt)
+        };
+
+        match &self.salt {
+            Some(salt) => encode(salt),
+            #[cfg(feature = "std")]
+            None => encode(&crate::generate_salt()),
+            #[cfg(not(feature = "std"))]
+            None => Err(crate::Error::InvalidSalt(
+                "a salt is required without the `std` feature".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{django_encode_password, Error};
+
+    #[test]
+    fn test_pbkdf2_sha256_builder() {
+        let encoded_password = Pbkdf2Sha256::new()
+            .iterations(1000)
+            .salt("seasalt")
+            .encode("hello")
+            .unwrap();
+        assert_eq!(
+            encoded_password,
+            django_encode_password("hello", "seasalt", 1000).unwrap()
+        );
+
+        let encoded_password = Pbkdf2Sha256::new()
+            .salt("btQDcwXF2RoK6Q")
+            .encode("hello")
+            .unwrap();
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+
+        let builder = Pbkdf2Sha256::new().salt("sea$salt");
+        assert!(matches!(
+            builder.encode("hello"),
+            Err(Error::InvalidSalt(_))
+        ));
+        let builder = Pbkdf2Sha256::new().salt("seasalt");
+        assert!(matches!(builder.encode(""), Err(Error::InvalidPassword(_))));
+
+        #[cfg(feature = "std")]
+        {
+            let builder = Pbkdf2Sha256::new().iterations(1000);
+            let encoded_password = builder.encode("hello").unwrap();
+            assert!(encoded_password.starts_with("pbkdf2_sha256$1000$"));
+            assert!(crate::django_auth("hello", &encoded_password).unwrap());
+            assert_ne!(builder.encode("hello").unwrap(), encoded_password);
+        }
+        #[cfg(not(feature = "std"))]
+        assert!(matches!(
+            Pbkdf2Sha256::new().encode("hello"),
+            Err(Error::InvalidSalt(_))
+        ));
+    }
+}
@@ -0,0 +1,131 @@
+//! C bindings, built with the `capi` feature, see `include/django_auth.h`.
+//!
+//! The functions never panic across the FFI boundary, errors (including
+//! panics) are reported as negative return codes.
+
+use core::ffi::{c_char, c_int, CStr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::Error;
+
+/// Success.
+pub const DJANGO_AUTH_OK: c_int = 0;
+/// A pointer argument is null.
+pub const DJANGO_AUTH_ERR_NULL_POINTER: c_int = -1;
+/// See [Error::InvalidEncodedPassword], also returned when the encoded
+/// password isn't valid UTF-8.
+pub const DJANGO_AUTH_ERR_INVALID_ENCODED_PASSWORD: c_int = -2;
+/// See [Error::UnsupportedAlgorithm].
+pub const DJANGO_AUTH_ERR_UNSUPPORTED_ALGORITHM: c_int = -3;
+/// See [Error::InvalidSalt].
+pub const DJANGO_AUTH_ERR_INVALID_SALT: c_int = -4;
+/// See [Error::InvalidPassword].
+pub const DJANGO_AUTH_ERR_INVALID_PASSWORD: c_int = -5;
+/// See [Error::AlgorithmNotAllowed].
+pub const DJANGO_AUTH_ERR_ALGORITHM_NOT_ALLOWED: c_int = -6;
+/// See [Error::IncorrectPassword].
+pub const DJANGO_AUTH_ERR_INCORRECT_PASSWORD: c_int = -7;
+/// See [Error::FeatureNotEnabled].
+pub const DJANGO_AUTH_ERR_FEATURE_NOT_ENABLED: c_int = -8;
+/// See [Error::InvalidIterations].
+pub const DJANGO_AUTH_ERR_INVALID_ITERATIONS: c_int = -9;
+/// See [Error::InvalidLength].
+pub const DJANGO_AUTH_ERR_INVALID_LENGTH: c_int = -10;
+/// An unexpected internal error.
+pub const DJANGO_AUTH_ERR_PANIC: c_int = -99;
+
+fn error_code(e: &Error) -> c_int {
+    match e {
+        Error::InvalidEncodedPassword(_) => DJANGO_AUTH_ERR_INVALID_ENCODED_PASSWORD,
+        Error::UnsupportedAlgorithm(_) => DJANGO_AUTH_ERR_UNSUPPORTED_ALGORITHM,
+        Error::InvalidSalt(_) => DJANGO_AUTH_ERR_INVALID_SALT,
+        Error::InvalidPassword(_) => DJANGO_AUTH_ERR_INVALID_PASSWORD,
+        Error::AlgorithmNotAllowed(_) => DJANGO_AUTH_ERR_ALGORITHM_NOT_ALLOWED,
+        Error::IncorrectPassword => DJANGO_AUTH_ERR_INCORRECT_PASSWORD,
+        Error::FeatureNotEnabled(_) => DJANGO_AUTH_ERR_FEATURE_NOT_ENABLED,
+        Error::InvalidIterations(_) => DJANGO_AUTH_ERR_INVALID_ITERATIONS,
+        Error::InvalidLength(_) => DJANGO_AUTH_ERR_INVALID_LENGTH,
+    }
+}
+
+/// Verify the NUL-terminated `password` against `encoded_password`, see
+/// [crate::django_auth], and store the result in `*out`.
+///
+/// Return [DJANGO_AUTH_OK], or a negative error code in which case `*out` is
+/// left untouched. The password doesn't have to be valid UTF-8 (see
+/// [crate::django_auth_bytes]).
+///
+/// # Safety
+///
+/// `password` and `encoded_password` must be null or point to NUL-terminated
+/// strings, and `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn django_auth_c(
+    password: *const c_char,
+    encoded_password: *const c_char,
+    out: *mut bool,
+) -> c_int {
+    if password.is_null() || encoded_password.is_null() || out.is_null() {
+        return DJANGO_AUTH_ERR_NULL_POINTER;
+    }
+    let password = CStr::from_ptr(password).to_bytes();
+    let Ok(encoded_password) = CStr::from_ptr(encoded_password).to_str() else {
+        return DJANGO_AUTH_ERR_INVALID_ENCODED_PASSWORD;
+    };
+
+    let res = catch_unwind(AssertUnwindSafe(|| {
+        crate::django_auth_bytes(password, encoded_password)
+    }));
+    match res {
+        Ok(Ok(matched)) => {
+            *out = matched;
+            DJANGO_AUTH_OK
+        }
+        Ok(Err(e)) => error_code(&e),
+        Err(_) => DJANGO_AUTH_ERR_PANIC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::ptr;
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn test_django_auth_c() {
+        let encoded = CString::new(
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        )
+        .unwrap();
+        let (hello, world) = (
+            CString::new("hello").unwrap(),
+            CString::new("world").unwrap(),
+        );
+
+        let mut out = false;
+        let res = unsafe { django_auth_c(hello.as_ptr(), encoded.as_ptr(), &mut out) };
+        assert_eq!(res, DJANGO_AUTH_OK);
+        assert!(out);
+        let res = unsafe { django_auth_c(world.as_ptr(), encoded.as_ptr(), &mut out) };
+        assert_eq!(res, DJANGO_AUTH_OK);
+        assert!(!out);
+
+        let invalid = CString::new("abc$edf").unwrap();
+        let res = unsafe { django_auth_c(hello.as_ptr(), invalid.as_ptr(), &mut out) };
+        assert_eq!(res, DJANGO_AUTH_ERR_UNSUPPORTED_ALGORITHM);
+        let invalid = CString::new(b"pbkdf2_sha256\xff".to_vec()).unwrap();
+        let res = unsafe { django_auth_c(hello.as_ptr(), invalid.as_ptr(), &mut out) };
+        assert_eq!(res, DJANGO_AUTH_ERR_INVALID_ENCODED_PASSWORD);
+
+        for (password, encoded, out) in [
+            (ptr::null(), encoded.as_ptr(), &mut out as *mut bool),
+            (hello.as_ptr(), ptr::null(), &mut out),
+            (hello.as_ptr(), encoded.as_ptr(), ptr::null_mut()),
+        ] {
+            let res = unsafe { django_auth_c(password, encoded, out) };
+            assert_eq!(res, DJANGO_AUTH_ERR_NULL_POINTER);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+use crate::hashers::pbkdf2::parse_iterations;
+use crate::{Algorithm, Error, Result};
+
+/// The components of a Django encoded password which uses the
+/// `<algorithm>$<iterations>$<salt>$<hash>` layout (e.g. pbkdf2_sha256), or
+/// the `<algorithm>$<salt>$<hash>` layout for algorithms without iterations.
+///
+/// Algorithms with other layouts (argon2, bcrypt, scrypt) can't be parsed
+/// into this type.
+///
+/// [fmt::Display] produces the encoded password back, so that
+/// `s.parse::<EncodedPassword>()?.to_string() == s` for any valid `s`, and the
+/// type can be stored in place of the `String`.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded: EncodedPassword =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+///         .parse()
+///         .expect("invalid encoded password");
+///
+/// assert_eq!(encoded.algorithm, "pbkdf2_sha256");
+/// assert_eq!(encoded.iterations, Some(180000));
+/// assert_eq!(encoded.salt, "btQDcwXF2RoK6Q");
+/// assert_eq!(encoded.hash, "D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=");
+/// ```
+///
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedPassword {
+    /// Algorithm name, e.g. "pbkdf2_sha256".
+    pub algorithm: String,
+    /// Number of iterations, if the algorithm has one.
+    pub iterations: Option<u32>,
+    pub salt: String,
+    /// The hash, as encoded by the algorithm (base64 for pbkdf2).
+    pub hash: String,
+}
+
+impl TryFrom<&str> for EncodedPassword {
+    type Error = Error;
+
+    fn try_from(encoded_password: &str) -> Result<Self> {
+        let parts: Vec<&str> = encoded_password.split('$').collect();
+        // known algorithms, custom ones may have iterations or not
+        let has_iterations = algorithm_has_iterations(parts[0]);
+        let (algorithm, iterations, salt, hash) = match parts[..] {
+            [algorithm, iterations, salt, hash] if has_iterations != Some(false) => {
+                (algorithm, Some(iterations), salt, hash)
+            }
+            [algorithm, salt, hash]
+                if has_iterations != Some(true) && !algorithm.starts_with("pbkdf2_") =>
+            {
+                (algorithm, None, salt, hash)
+            }
+            [algorithm, _, _, _] => {
+                return Err(Error::InvalidEncodedPassword(format!(
+                    "{algorithm} encoded passwords have no iterations"
+                )))
+            }
+            _ => {
+                return Err(Error::InvalidEncodedPassword(
+                    "encoded password should have 4 (or 3 if there are no iterations) \
+                     components separated by '$'"
+                        .to_owned(),
+                ))
+            }
+        };
+
+        if algorithm.is_empty() {
+            return Err(Error::InvalidEncodedPassword(
+                "algorithm should not be empty".to_owned(),
+            ));
+        }
+
+        let iterations = iterations.map(parse_iterations).transpose()?;
+
+        Ok(Self {
+            algorithm: algorithm.to_owned(),
+            iterations,
+            salt: salt.to_owned(),
+            hash: hash.to_owned(),
+        })
+    }
+}
+
+/// Parse raw bytes, e.g. a text column read by a database driver, which must
+/// be valid UTF-8.
+impl TryFrom<&[u8]> for EncodedPassword {
+    type Error = Error;
+
+    fn try_from(encoded_password: &[u8]) -> Result<Self> {
+        let encoded_password = core::str::from_utf8(encoded_password).map_err(|e| {
+            Error::InvalidEncodedPassword(format!("encoded password is not valid UTF-8: {e}"))
+        })?;
+        Self::try_from(encoded_password)
+    }
+}
+
+/// Whether the known `algorithm` has iterations, `None` for unknown ones.
+fn algorithm_has_iterations(algorithm: &str) -> Option<bool> {
+    algorithm
+        .parse::<Algorithm>()
+        .ok()
+        .map(|algorithm| algorithm.has_iterations())
+}
+
+impl FromStr for EncodedPassword {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s)
+    }
+}
+
+impl fmt::Display for EncodedPassword {
+    /// Format as the Django encoded password, i.e. the inverse of parsing.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.iterations {
+            Some(iterations) => write!(
+                f,
+                "{}${}${}${}",
+                self.algorithm, iterations, self.salt, self.hash
+            ),
+            None => write!(f, "{}${}${}", self.algorithm, self.salt, self.hash),
+        }
+    }
+}
+
+/// Serialized as the Django encoded password string.
+#[cfg(feature = "serde")]
+impl serde::Serialize for EncodedPassword {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> core::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Deserialized from a Django encoded password string, which is validated
+/// like [EncodedPassword::try_from] does.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EncodedPassword {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> core::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encoded_password() {
+        let encoded = EncodedPassword::try_from(
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+        )
+        .unwrap();
+        assert_eq!(
+            encoded,
+            EncodedPassword {
+                algorithm: "pbkdf2_sha1".to_owned(),
+                iterations: Some(180000),
+                salt: "btQDcwXF2RoK6Q".to_owned(),
+                hash: "6JOEcGwOZsGrn0ysuB/Sp20EBx8=".to_owned(),
+            }
+        );
+
+        // no iterations
+        let encoded: EncodedPassword = "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8"
+            .parse()
+            .unwrap();
+        assert_eq!(encoded.algorithm, "sha1");
+        assert_eq!(encoded.iterations, None);
+        assert_eq!(encoded.salt, "seasalt");
+
+        // too few components
+        for s in ["", "pbkdf2_sha256", "abc$edf", "pbkdf2_sha256$salt$hash"] {
+            assert!(matches!(
+                s.parse::<EncodedPassword>(),
+                Err(Error::InvalidEncodedPassword(_))
+            ));
+        }
+
+        // too many components
+        let res: Result<EncodedPassword> =
+            "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==".parse();
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        // iterations of algorithms which have none, or the other way around
+        for s in [
+            "sha1$1000$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+            "md5$1000$seasalt$3f86d0d3d465b7b458c231bf3555c0e3",
+            "pbkdf2_sha1$seasalt$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+        ] {
+            assert!(
+                matches!(
+                    s.parse::<EncodedPassword>(),
+                    Err(Error::InvalidEncodedPassword(_))
+                ),
+                "{s}"
+            );
+        }
+        // unknown algorithms may have iterations or not
+        let encoded: EncodedPassword = "custom$1000$salt$hash".parse().unwrap();
+        assert_eq!(encoded.iterations, Some(1000));
+        let encoded: EncodedPassword = "custom$salt$hash".parse().unwrap();
+        assert_eq!(encoded.iterations, None);
+
+        for s in [
+            "pbkdf2_sha256$abc$salt$hash",
+            "pbkdf2_sha256$+180000$salt$hash",
+            "custom$ 1000$salt$hash",
+            "pbkdf2_sha256$99999999999$salt$hash",
+        ] {
+            let res: Result<EncodedPassword> = s.parse();
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))), "{s}");
+        }
+
+        let res: Result<EncodedPassword> = "$180000$salt$hash".parse();
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+    }
+
+    #[test]
+    fn test_encoded_password_from_bytes() {
+        let s = "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=";
+        assert_eq!(
+            EncodedPassword::try_from(s.as_bytes()).unwrap(),
+            EncodedPassword::try_from(s).unwrap()
+        );
+
+        for bytes in [
+            &b"pbkdf2_sha256$180000$\xffsalt$hash"[..],
+            b"\xc3",
+            b"pbkdf2_sha256$180000$salt$hash\xed\xa0\x80",
+        ] {
+            let res = EncodedPassword::try_from(bytes);
+            assert!(
+                matches!(&res, Err(Error::InvalidEncodedPassword(e)) if e.contains("UTF-8")),
+                "{res:?}"
+            );
+        }
+
+        // valid UTF-8 but malformed
+        let res = EncodedPassword::try_from(&b"pbkdf2_sha256$salt"[..]);
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+    }
+
+    #[test]
+    fn test_encoded_password_display() {
+        for s in [
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+            "pbkdf2_sha256$1000$$hash",
+            "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+            "sha1$$6d138ca3ae545631b3abd71a4f076ce759c5700b",
+            "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3",
+            "md5$$88a434c88cca4e900f7874cd98123f43",
+            "custom$0$salt$hash",
+        ] {
+            let encoded: EncodedPassword = s.parse().unwrap();
+            assert_eq!(encoded.to_string(), s);
+            assert_eq!(
+                encoded.to_string().parse::<EncodedPassword>().unwrap(),
+                encoded
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_encoded_password_serde() {
+        let s = "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        let encoded: EncodedPassword = s.parse().unwrap();
+
+        let json = serde_json::to_string(&encoded).unwrap();
+        assert_eq!(json, format!("\"{s}\""));
+        assert_eq!(
+            serde_json::from_str::<EncodedPassword>(&json).unwrap(),
+            encoded
+        );
+
+        assert!(
+            serde_json::from_str::<EncodedPassword>("\"pbkdf2_sha256$abc$salt$hash\"").is_err()
+        );
+        assert!(serde_json::from_str::<EncodedPassword>("180000").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use base64::prelude::*;
+use zeroize::Zeroizing;
+
+use super::{check_memory_cost, check_salt, strip_algorithm, EncodedParts, Hasher};
+use crate::{constant_time_eq, Error, Result};
+
+/// Django's `Argon2PasswordHasher`:
+///
+/// `argon2$argon2id$v=19$m=102400,t=2,p=8$<salt>$<hash>`
+///
+/// Salt and hash are base64 encoded without padding, like argon2-cffi does.
+#[derive(Debug, Clone)]
+pub struct Argon2Hasher {
+    /// Memory size in KiB.
+    pub memory_cost: u32,
+    /// Number of iterations.
+    pub time_cost: u32,
+    /// Degree of parallelism.
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Hasher {
+    /// Django's default parameters.
+    fn default() -> Self {
+        Self {
+            memory_cost: 102400,
+            time_cost: 2,
+            parallelism: 8,
+        }
+    }
+}
+
+impl Hasher for Argon2Hasher {
+    fn algorithm(&self) -> &str {
+        "argon2"
+    }
+
+    /// Encode `password` with argon2id version 19 and a 32 bytes hash, like
+    /// Django does.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    /// Hashes created by old versions of argon2-cffi may have no version
+    /// component, these are treated as version 0x10.
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        check_salt(salt)?;
+
+        let params =
+            argon2::Params::new(self.memory_cost, self.time_cost, self.parallelism, Some(32))
+                .map_err(|e| Error::InvalidIterations(format!("invalid argon2 parameters: {e}")))?;
+
+        let mut hash = Zeroizing::new([0u8; 32]);
+        argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+            .hash_password_into(password, salt.as_bytes(), hash.as_mut())
+            .map_err(|e| match e {
+                argon2::Error::SaltTooShort | argon2::Error::SaltTooLong => {
+                    Error::InvalidSalt(format!("argon2 error: {e}"))
+                }
+                argon2::Error::PwdTooLong => Error::InvalidPassword(format!("argon2 error: {e}")),
+                _ => Error::InvalidIterations(format!("argon2 error: {e}")),
+            })?;
+
+        Ok(format!(
+            "{}$argon2id$v=19$m={},t={},p={}${}${}",
+            self.algorithm(),
+            self.memory_cost,
+            self.time_cost,
+            self.parallelism,
+            BASE64_STANDARD_NO_PAD.encode(salt),
+            BASE64_STANDARD_NO_PAD.encode(hash.as_ref()),
+        ))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let decoded = decode(&self.split(encoded)?)?;
+        // each pass fills the whole memory
+        check_memory_cost(
+            self.algorithm(),
+            u64::from(decoded.memory_cost) * u64::from(decoded.time_cost),
+        )?;
+
+        let params = argon2::Params::new(
+            decoded.memory_cost,
+            decoded.time_cost,
+            decoded.parallelism,
+            Some(decoded.hash.len()),
+        )
+        .map_err(|e| Error::InvalidEncodedPassword(format!("invalid argon2 parameters: {e}")))?;
+
+        let mut computed = Zeroizing::new(vec![0u8; decoded.hash.len()]);
+        argon2::Argon2::new(decoded.algorithm, decoded.version, params)
+            .hash_password_into(password, &decoded.salt, &mut computed)
+            .map_err(|e| Error::InvalidEncodedPassword(format!("argon2 error: {e}")))?;
+
+        Ok(constant_time_eq(&computed, &decoded.hash))
+    }
+
+    /// `argon2$<variety>$v=<version>$m=<memory cost>,t=<time cost>,p=<parallelism>$<salt>$<hash>`,
+    /// the version being optional.
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        let mut params: Vec<&str> = strip_algorithm(self.algorithm(), encoded)?
+            .split('$')
+            .collect();
+        let (Some(hash), Some(salt), 2..=3) = (params.pop(), params.pop(), params.len()) else {
+            return Err(Error::InvalidEncodedPassword(
+                "argon2 encoded password should have 5 or 6 components separated by '$'".to_owned(),
+            ));
+        };
+        Ok(EncodedParts { params, salt, hash })
+    }
+
+    /// Encoded passwords not using argon2id version 19, or with a lower memory
+    /// cost, time cost or parallelism need an update.
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        let decoded = decode(&self.split(encoded)?)?;
+        Ok(decoded.algorithm != argon2::Algorithm::Argon2id
+            || decoded.version != argon2::Version::V0x13
+            || decoded.memory_cost < self.memory_cost
+            || decoded.time_cost < self.time_cost
+            || decoded.parallelism < self.parallelism)
+    }
+}
+
+/// The components of an argon2 encoded password.
+struct Decoded {
+    algorithm: argon2::Algorithm,
+    version: argon2::Version,
+    memory_cost: u32,
+    time_cost: u32,
+    parallelism: u32,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+/// Parse the components of an argon2 encoded password.
+fn decode(parts: &EncodedParts<'_>) -> Result<Decoded> {
+    let (variety, version, params) = match parts.params[..] {
+        [variety, version, params] => (variety, Some(version), params),
+        [variety, params] => (variety, None, params),
+        _ => {
+            return Err(Error::InvalidEncodedPassword(
+                "argon2 encoded password should have 5 or 6 components separated by '$'".to_owned(),
+            ))
+        }
+    };
+
+    let algorithm: argon2::Algorithm = variety
+        .parse()
+        .map_err(|_| Error::UnsupportedAlgorithm(format!("argon2 variety {variety}")))?;
+
+    let version = match version {
+        None | Some("v=16") => argon2::Version::V0x10,
+        Some("v=19") => argon2::Version::V0x13,
+        Some(v) => return Err(Error::UnsupportedAlgorithm(format!("argon2 version {v}"))),
+    };
+
+    let (mut memory_cost, mut time_cost, mut parallelism) = (None, None, None);
+    for param in params.split(',') {
+        let (key, value) = param.split_once('=').ok_or_else(|| {
+            Error::InvalidEncodedPassword(format!("invalid argon2 parameter: {param}"))
+        })?;
+        let value: u32 = value.parse().map_err(|_| {
+            Error::InvalidEncodedPassword(format!("invalid argon2 parameter: {param}"))
+        })?;
+        match key {
+            "m" => memory_cost = Some(value),
+            "t" => time_cost = Some(value),
+            "p" => parallelism = Some(value),
+            _ => {
+                return Err(Error::InvalidEncodedPassword(format!(
+                    "unknown argon2 parameter: {param}"
+                )))
+            }
+        }
+    }
+    let (Some(memory_cost), Some(time_cost), Some(parallelism)) =
+        (memory_cost, time_cost, parallelism)
+    else {
+        return Err(Error::InvalidEncodedPassword(
+            "argon2 parameters should contain m, t and p".to_owned(),
+        ));
+    };
+
+    let salt = BASE64_STANDARD_NO_PAD
+        .decode(parts.salt)
+        .map_err(|_| Error::InvalidSalt("argon2 salt is not valid base64".into()))?;
+    let hash = BASE64_STANDARD_NO_PAD
+        .decode(parts.hash)
+        .map_err(|_| Error::InvalidEncodedPassword("argon2 hash is not valid base64".to_owned()))?;
+
+    Ok(Decoded {
+        algorithm,
+        version,
+        memory_cost,
+        time_cost,
+        parallelism,
+        salt,
+        hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_django_auth_argon2() {
+        // taken from Django's test suite (auth_tests/test_hashers.py)
+        let encoded = "argon2$argon2i$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q";
+        assert!(django_auth("secret", encoded).unwrap());
+        assert!(!django_auth("wrong", encoded).unwrap());
+
+        // old hash without version attribute
+        let encoded = "argon2$argon2i$m=8,t=1,p=1$c29tZXNhbHQ$gwQOXSNhxiOxPOA0+PY10P9QFO\
+                       4NAYysnqRt1GSQLE55m+2GYDt9FEjPMHhP2Cuf0nOEXXMocVrsJAtNSsKyfg";
+        assert!(django_auth("secret", encoded).unwrap());
+        assert!(!django_auth("wrong", encoded).unwrap());
+
+        let res = django_auth(
+            "secret",
+            "argon2$argon2i$m=8,t=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+        );
+        assert!(res.is_err());
+
+        let res = django_auth("secret", "argon2$argon2x$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9");
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_argon2_max_memory_cost() {
+        // rejected before allocating terabytes or hashing for hours
+        for params in [
+            "m=4294967295,t=1,p=1",
+            "m=8,t=4294967295,p=1",
+            "m=1048577,t=2,p=8",
+        ] {
+            let res = django_auth(
+                "secret",
+                format!("argon2$argon2id$v=19${params}$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q"),
+            );
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{params}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_argon2_encode() {
+        let hasher = Argon2Hasher {
+            memory_cost: 8,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let encoded = hasher.encode("l\u{e8}tmein", "abcdefghijk").unwrap();
+        assert!(encoded.starts_with("argon2$argon2id$v=19$m=8,t=1,p=1$YWJjZGVmZ2hpams$"));
+        assert!(hasher.verify("l\u{e8}tmein", &encoded).unwrap());
+        assert!(!hasher.verify("letmein", &encoded).unwrap());
+
+        // same as the PHC string argon2-cffi produces, prefixed by "argon2"
+        let phc_hasher = argon2::Argon2::new(
+            argon2::Algorithm::Argon2id,
+            argon2::Version::V0x13,
+            argon2::Params::new(8, 1, 1, Some(32)).unwrap(),
+        );
+        let salt = argon2::password_hash::SaltString::encode_b64(b"abcdefghijk").unwrap();
+        let phc =
+            argon2::PasswordHasher::hash_password(&phc_hasher, "l\u{e8}tmein".as_bytes(), &salt)
+                .unwrap();
+        assert_eq!(encoded, format!("argon2{phc}"));
+
+        // argon2 requires salts of at least 8 bytes
+        assert!(matches!(
+            hasher.encode("l\u{e8}tmein", "abc"),
+            Err(Error::InvalidSalt(_))
+        ));
+    }
+
+    #[test]
+    fn test_argon2_must_update() {
+        let hasher = Argon2Hasher {
+            memory_cost: 8,
+            time_cost: 2,
+            parallelism: 1,
+        };
+        let encoded = hasher.encode("secret", "abcdefghijk").unwrap();
+        assert!(!hasher.must_update(&encoded).unwrap());
+        assert!(Argon2Hasher::default().must_update(&encoded).unwrap());
+        let weaker = Argon2Hasher {
+            time_cost: 1,
+            ..hasher.clone()
+        };
+        assert!(!weaker.must_update(&encoded).unwrap());
+
+        // argon2i, and argon2i without version
+        let encoded = "argon2$argon2i$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q";
+        assert!(hasher.must_update(encoded).unwrap());
+        let encoded = "argon2$argon2id$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q";
+        assert!(hasher.must_update(encoded).unwrap());
+
+        assert!(hasher.must_update("argon2$argon2id$v=19$m=8").is_err());
+    }
+
+    #[test]
+    fn test_argon2_split() {
+        let parts = Argon2Hasher::default()
+            .split("argon2$argon2i$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q")
+            .unwrap();
+        assert_eq!(parts.params, ["argon2i", "v=19", "m=8,t=2,p=1"]);
+        assert_eq!(parts.salt, "c2FsdHNhbHQ");
+        assert_eq!(parts.hash, "YC9+jJCrQhs5R6db7LlN8Q");
+
+        // no version
+        let parts = Argon2Hasher::default()
+            .split("argon2$argon2i$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q")
+            .unwrap();
+        assert_eq!(parts.params, ["argon2i", "m=8,t=2,p=1"]);
+        assert_eq!(parts.salt, "c2FsdHNhbHQ");
+
+        for encoded in [
+            "argon2$argon2i$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+            "argon2$argon2i$v=19$m=8,t=2,p=1$x$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+        ] {
+            assert!(Argon2Hasher::default().split(encoded).is_err(), "{encoded}");
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use base64::alphabet::BCRYPT;
+use base64::engine::general_purpose::{GeneralPurpose, NO_PAD};
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+use super::{hex, strip_algorithm, EncodedParts, Hasher};
+use crate::{Error, Result};
+
+/// Django's `BCryptSHA256PasswordHasher`:
+///
+/// `bcrypt_sha256$$2b$12$<salt><hash>`
+///
+/// Note the double `$`: the bcrypt hash itself starts with `$`.
+#[derive(Debug, Clone)]
+pub struct BcryptSha256Hasher {
+    /// Cost factor (log2 of the number of rounds).
+    pub cost: u32,
+}
+
+impl Default for BcryptSha256Hasher {
+    fn default() -> Self {
+        Self { cost: 12 }
+    }
+}
+
+impl Hasher for BcryptSha256Hasher {
+    fn algorithm(&self) -> &str {
+        "bcrypt_sha256"
+    }
+
+    /// `salt` is the 22 characters bcrypt salt.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        let hash = hash_with_salt(sha256_hex(password).as_bytes(), self.cost, salt)?;
+        Ok(format!("{}${}", self.algorithm(), hash))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let bcrypt_hash = strip_algorithm(self.algorithm(), encoded)?;
+        verify(sha256_hex(password).as_bytes(), bcrypt_hash)
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        Ok(cost(&self.split(encoded)?)? < self.cost)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        split(strip_algorithm(self.algorithm(), encoded)?)
+    }
+}
+
+/// Django's `BCryptPasswordHasher`:
+///
+/// `bcrypt$$2b$12$<salt><hash>`
+///
+/// bcrypt only uses the first 72 bytes of the password, see
+/// [django_encode_password_bcrypt](crate::django_encode_password_bcrypt).
+#[derive(Debug, Clone)]
+pub struct BcryptHasher {
+    /// Cost factor (log2 of the number of rounds).
+    pub cost: u32,
+}
+
+impl Default for BcryptHasher {
+    fn default() -> Self {
+        Self { cost: 12 }
+    }
+}
+
+impl Hasher for BcryptHasher {
+    fn algorithm(&self) -> &str {
+        "bcrypt"
+    }
+
+    /// `salt` is the 22 characters bcrypt salt.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        let hash = hash_with_salt(password, self.cost, salt)?;
+        Ok(format!("{}${}", self.algorithm(), hash))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let bcrypt_hash = strip_algorithm(self.algorithm(), encoded)?;
+        verify(password, bcrypt_hash)
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        Ok(cost(&self.split(encoded)?)? < self.cost)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        split(strip_algorithm(self.algorithm(), encoded)?)
+    }
+}
+
+/// Django pre-hashes the password with SHA256 to avoid bcrypt's 72 bytes
+/// truncation, and passes the hex digest (not the raw bytes) to bcrypt.
+fn sha256_hex(password: &[u8]) -> Zeroizing<String> {
+    let mut digest = Sha256::digest(password);
+    let hex = Zeroizing::new(hex(&digest));
+    digest.zeroize();
+    hex
+}
+
+/// bcrypt's own base64 flavor
+const BCRYPT_BASE64: GeneralPurpose = GeneralPurpose::new(&BCRYPT, NO_PAD);
+
+/// Generate a random 22 characters bcrypt salt, i.e. 16 bytes in bcrypt's
+/// base64 flavor.
+pub(crate) fn generate_salt() -> String {
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    BCRYPT_BASE64.encode(salt)
+}
+
+fn hash_with_salt(password: impl AsRef<[u8]>, cost: u32, salt: &str) -> Result<String> {
+    if !(4..=31).contains(&cost) {
+        return Err(Error::InvalidIterations(format!(
+            "bcrypt cost should be between 4 and 31, got {cost}"
+        )));
+    }
+    let salt: [u8; 16] = BCRYPT_BASE64
+        .decode(salt)
+        .ok()
+        .and_then(|salt| salt.try_into().ok())
+        .ok_or_else(|| Error::InvalidSalt("expect a 22 characters bcrypt salt".into()))?;
+
+    let parts = bcrypt::hash_with_salt(password, cost, salt)
+        .map_err(|e| Error::InvalidEncodedPassword(format!("bcrypt error: {e}")))?;
+
+    Ok(parts.format_for_version(bcrypt::Version::TwoB))
+}
+
+/// Split `bcrypt_hash` (`$2b$<cost>$<salt (22 characters)><hash>`), the
+/// parameters being the bcrypt version and the cost.
+fn split(bcrypt_hash: &str) -> Result<EncodedParts<'_>> {
+    let parts: Vec<&str> = bcrypt_hash.split('$').collect();
+    match parts[..] {
+        ["", version, cost, salt_hash] if salt_hash.len() == 53 && salt_hash.is_ascii() => {
+            let (salt, hash) = salt_hash.split_at(22);
+            Ok(EncodedParts {
+                params: vec![version, cost],
+                salt,
+                hash,
+            })
+        }
+        _ => Err(Error::InvalidEncodedPassword(
+            "invalid bcrypt hash".to_owned(),
+        )),
+    }
+}
+
+/// Parse the cost of a split bcrypt hash.
+fn cost(parts: &EncodedParts<'_>) -> Result<u32> {
+    let cost = parts.params.get(1).copied().unwrap_or_default();
+    cost.parse()
+        .map_err(|_| Error::InvalidEncodedPassword(format!("invalid bcrypt cost: {cost}")))
+}
+
+/// Reject costs above [max_bcrypt_cost](crate::max_bcrypt_cost), each
+/// increment doubling the verification time.
+fn check_cost(cost: u32) -> Result<()> {
+    let max = crate::max_bcrypt_cost();
+    if cost > max {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "bcrypt cost should not be more than {max}, got {cost}"
+        )));
+    }
+    Ok(())
+}
+
+fn verify(password: impl AsRef<[u8]>, bcrypt_hash: &str) -> Result<bool> {
+    check_cost(cost(&split(bcrypt_hash)?)?)?;
+    bcrypt::verify(password, bcrypt_hash)
+        .map_err(|e| Error::InvalidEncodedPassword(format!("bcrypt error: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{django_auth, django_encode_password_bcrypt};
+
+    #[test]
+    fn test_django_auth_bcrypt_sha256() {
+        // bcrypt.hashpw(hexlify(sha256("lètmein")), salt) with 4 rounds
+        let encoded = "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let res = django_auth("l\u{e8}tmein", "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRK");
+        assert!(res.is_err());
+
+        let hasher = BcryptSha256Hasher { cost: 4 };
+        let res = hasher.encode("l\u{e8}tmein", "Ju3n0NxMoXrbC2LMcRKDXu");
+        assert_eq!(res.unwrap(), encoded);
+    }
+
+    #[test]
+    fn test_django_auth_bcrypt() {
+        // bcrypt.hashpw("lètmein", salt) with 4 rounds
+        let encoded = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = BcryptHasher { cost: 4 };
+        let res = hasher.encode("l\u{e8}tmein", "Ju3n0NxMoXrbC2LMcRKDXu");
+        assert_eq!(res.unwrap(), encoded);
+        assert!(hasher.encode("l\u{e8}tmein", "Ju3n0NxMoX").is_err());
+
+        // passwords are truncated to 72 bytes
+        let password = "a".repeat(72);
+        let encoded = django_encode_password_bcrypt(&password, 4).unwrap();
+        assert!(encoded.starts_with("bcrypt$$2b$04$"));
+        assert!(django_auth(&password, &encoded).unwrap());
+        assert!(django_auth(format!("{password}bbb"), &encoded).unwrap());
+        assert!(!django_auth(&password[1..], &encoded).unwrap());
+    }
+
+    #[test]
+    fn test_bcrypt_max_cost() {
+        // rejected before hashing, 2^20 rounds would take about a minute
+        for encoded in [
+            "bcrypt$$2b$20$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+            "bcrypt$$2b$31$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+            "bcrypt_sha256$$2b$20$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy",
+        ] {
+            let res = django_auth("l\u{e8}tmein", encoded);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_django_encode_password_bcrypt() {
+        let a = django_encode_password_bcrypt("hello", 4).unwrap();
+        let b = django_encode_password_bcrypt("hello", 4).unwrap();
+        // random salts
+        assert_ne!(a, b);
+        assert!(django_auth("hello", &a).unwrap());
+        assert!(django_auth("hello", &b).unwrap());
+
+        let res = django_encode_password_bcrypt("", 4);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_bcrypt("a".repeat(crate::max_password_length() + 1), 4);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+
+        for cost in [1, 3, 32, u32::MAX] {
+            let res = django_encode_password_bcrypt("hello", cost);
+            assert!(matches!(res, Err(Error::InvalidIterations(_))), "{cost}");
+            let res = BcryptSha256Hasher { cost }.encode("hello", "Ju3n0NxMoXrbC2LMcRKDXu");
+            assert!(matches!(res, Err(Error::InvalidIterations(_))), "{cost}");
+        }
+    }
+
+    #[test]
+    fn test_bcrypt_must_update() {
+        let encoded = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza";
+        assert!(!BcryptHasher { cost: 4 }.must_update(encoded).unwrap());
+        assert!(!BcryptHasher { cost: 3 }.must_update(encoded).unwrap());
+        assert!(BcryptHasher::default().must_update(encoded).unwrap());
+
+        let encoded = "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy";
+        assert!(!BcryptSha256Hasher { cost: 4 }.must_update(encoded).unwrap());
+        assert!(BcryptSha256Hasher::default().must_update(encoded).unwrap());
+
+        for encoded in [
+            "bcrypt$$2b$xx$Ju3n0NxMoXrbC2LMcRKDXu",
+            "bcrypt$2b$04",
+            "bcrypt$",
+        ] {
+            assert!(
+                BcryptHasher::default().must_update(encoded).is_err(),
+                "{encoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_bcrypt_split() {
+        let encoded = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza";
+        let parts = BcryptHasher::default().split(encoded).unwrap();
+        assert_eq!(parts.params, ["2b", "04"]);
+        assert_eq!(parts.salt, "Ju3n0NxMoXrbC2LMcRKDXu");
+        assert_eq!(parts.hash, "JIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza");
+
+        let encoded = "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy";
+        let parts = BcryptSha256Hasher::default().split(encoded).unwrap();
+        assert_eq!(parts.salt, "Ju3n0NxMoXrbC2LMcRKDXu");
+        assert_eq!(parts.hash, "XycFwEHjpWlx10LDgON9B7k6MWMkXqy");
+        assert!(BcryptHasher::default().split(encoded).is_err());
+
+        // truncated, or pbkdf2-style
+        for encoded in [
+            "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXu",
+            "bcrypt$04$Ju3n0NxMoXrbC2LMcRKDXu$JIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+        ] {
+            assert!(BcryptHasher::default().split(encoded).is_err(), "{encoded}");
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use zeroize::Zeroize;
+
+use super::{strip_algorithm, EncodedParts, Hasher};
+use crate::{constant_time_eq, Error, Result};
+
+/// Django's `CryptPasswordHasher`, the traditional DES based Unix `crypt(3)`:
+///
+/// `crypt$$<salt (2 characters)><hash (11 characters)>`
+///
+/// Only the first 8 bytes of the password are used, and only 7 bits of each.
+///
+/// **Insecure**, only useful to migrate passwords from very old Django
+/// versions (requires the `insecure-hashers` feature). Unlike Django, which
+/// calls the system `crypt(3)`, this is a pure Rust implementation of the
+/// traditional algorithm, the glibc extensions (`$1$`, `$5$`...) are not
+/// supported.
+#[derive(Debug, Clone, Default)]
+pub struct CryptHasher;
+
+impl Hasher for CryptHasher {
+    fn algorithm(&self) -> &str {
+        "crypt"
+    }
+
+    /// `salt` is made of 2 characters of `[./0-9A-Za-z]`.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        let salt: [u8; 2] = salt
+            .as_bytes()
+            .try_into()
+            .map_err(|_| Error::InvalidSalt("crypt salt should be 2 characters".into()))?;
+        let hash = crypt(password, salt)?;
+        Ok(format!("{}$${}", self.algorithm(), hash))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let parts = self.split(encoded)?;
+        let salt = [parts.salt.as_bytes()[0], parts.salt.as_bytes()[1]];
+        let computed = crypt(password, salt)?;
+        Ok(constant_time_eq(
+            &computed.as_bytes()[2..],
+            parts.hash.as_bytes(),
+        ))
+    }
+
+    /// Like Django, the salt component is ignored (it's always empty), the
+    /// salt being the first 2 characters of the crypt output.
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        let data = strip_algorithm(self.algorithm(), encoded)?
+            .split_once('$')
+            .map(|(_, data)| data)
+            .filter(|data| data.len() == 13 && data.is_ascii())
+            .ok_or_else(|| {
+                Error::InvalidEncodedPassword(
+                    "crypt encoded password should be crypt$$ followed by 13 characters".to_owned(),
+                )
+            })?;
+        let (salt, hash) = data.split_at(2);
+        Ok(EncodedParts {
+            params: Vec::new(),
+            salt,
+            hash,
+        })
+    }
+}
+
+/// The alphabet of the crypt salts and hashes.
+const ALPHABET: &[u8; 64] = b"./0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// The traditional `crypt(3)`: the salt perturbs the DES expansion, and a
+/// zero block is encrypted 25 times with the password as key.
+fn crypt(password: &[u8], salt: [u8; 2]) -> Result<String> {
+    if password.contains(&0) {
+        // crypt(3) takes a C string
+        return Err(Error::InvalidPassword(
+            "crypt password should not contain NUL bytes".into(),
+        ));
+    }
+
+    let mut expansion = E;
+    for (i, c) in salt.iter().enumerate() {
+        let bits = ALPHABET.iter().position(|a| a == c).ok_or_else(|| {
+            Error::InvalidSalt("crypt salt should only contain [./0-9A-Za-z]".into())
+        })?;
+        for j in 0..6 {
+            if bits >> j & 1 == 1 {
+                expansion.swap(6 * i + j, 6 * i + j + 24);
+            }
+        }
+    }
+
+    // 7 bits of each of the first 8 bytes, leaving the parity bits out
+    let mut key = [0u8; 8];
+    for (k, p) in key.iter_mut().zip(password) {
+        *k = p << 1;
+    }
+    let mut subkeys = key_schedule(u64::from_be_bytes(key));
+    key.zeroize();
+
+    let mut block = 0;
+    for _ in 0..25 {
+        block = des_encrypt(block, &subkeys, &expansion);
+    }
+    subkeys.zeroize();
+
+    // 64 bits, padded to 66, in 11 characters
+    let block = u128::from(block) << 2;
+    let mut hash = String::with_capacity(13);
+    hash.extend(salt.iter().map(|&c| char::from(c)));
+    hash.extend((0..11).map(|i| char::from(ALPHABET[(block >> (60 - 6 * i)) as usize & 63])));
+    Ok(hash)
+}
+
+/// Permute the `width` bits `input` with `table`, whose entries are the
+/// 1-based input bit numbers (from the most significant bit) of each output
+/// bit, the DES tables convention.
+fn permute(input: u64, width: u32, table: &[u8]) -> u64 {
+    table.iter().fold(0, |output, &bit| {
+        output << 1 | input >> (width - bit as u32) & 1
+    })
+}
+
+fn key_schedule(key: u64) -> [u64; 16] {
+    const MASK: u64 = (1 << 28) - 1;
+    let key = permute(key, 64, &PC1);
+    let (mut c, mut d) = (key >> 28, key & MASK);
+    let mut subkeys = [0; 16];
+    for (subkey, &shift) in subkeys.iter_mut().zip(&SHIFTS) {
+        c = (c << shift | c >> (28 - shift)) & MASK;
+        d = (d << shift | d >> (28 - shift)) & MASK;
+        *subkey = permute(c << 28 | d, 56, &PC2);
+    }
+    subkeys
+}
+
+fn des_encrypt(block: u64, subkeys: &[u64; 16], expansion: &[u8; 48]) -> u64 {
+    let block = permute(block, 64, &IP);
+    let (mut l, mut r) = (block >> 32, block & 0xffff_ffff);
+    for subkey in subkeys {
+        (l, r) = (r, l ^ feistel(r, *subkey, expansion));
+    }
+    permute(r << 32 | l, 64, &FP)
+}
+
+fn feistel(r: u64, subkey: u64, expansion: &[u8; 48]) -> u64 {
+    let e = permute(r, 32, expansion) ^ subkey;
+    let s = S.iter().enumerate().fold(0, |s, (i, sbox)| {
+        let six = (e >> (42 - 6 * i)) & 0x3f;
+        let row = (six & 0x20) >> 4 | six & 1;
+        let col = (six >> 1) & 0xf;
+        s << 4 | u64::from(sbox[(row * 16 + col) as usize])
+    });
+    permute(s, 32, &P)
+}
+
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, //
+    62, 54, 46, 38, 30, 22, 14, 6, 64, 56, 48, 40, 32, 24, 16, 8, //
+    57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, //
+    61, 53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, //
+    38, 6, 46, 14, 54, 22, 62, 30, 37, 5, 45, 13, 53, 21, 61, 29, //
+    36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27, //
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, //
+    8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, //
+    16, 17, 18, 19, 20, 21, 20, 21, 22, 23, 24, 25, //
+    24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, //
+    2, 8, 24, 14, 32, 27, 3, 9, 19, 13, 30, 6, 22, 11, 4, 25,
+];
+
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, //
+    10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36, //
+    63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, //
+    14, 6, 61, 53, 45, 37, 29, 21, 13, 5, 28, 20, 12, 4,
+];
+
+const PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, //
+    23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, //
+    41, 52, 31, 37, 47, 55, 30, 40, 51, 45, 33, 48, //
+    44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+const S: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, //
+        0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8, //
+        4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, //
+        15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, //
+        3, 13, 4, 7, 15, 2, 8, 14, 12, 0, 1, 10, 6, 9, 11, 5, //
+        0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, //
+        13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, //
+        13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1, //
+        13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, //
+        1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, //
+        13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9, //
+        10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, //
+        3, 15, 0, 6, 10, 1, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14,
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, //
+        14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6, //
+        4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, //
+        11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, //
+        10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8, //
+        9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, //
+        4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, //
+        13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6, //
+        1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, //
+        6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, //
+        1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2, //
+        7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, //
+        2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_des() {
+        // the classic worked example of the DES specification
+        let subkeys = key_schedule(0x1334_5779_9bbc_dff1);
+        assert_eq!(
+            des_encrypt(0x0123_4567_89ab_cdef, &subkeys, &E),
+            0x85e8_1354_0f0a_b405
+        );
+    }
+
+    #[test]
+    fn test_crypt() {
+        // crypt.crypt(password, salt) with glibc
+        for (password, salt, hash) in [
+            ("l\u{e8}tmein", "ab", "abprj99vCww4M"),
+            ("hello", "se", "seCNzjgcRw51Q"),
+            ("", "ab", "abmF1QH4PEr.E"),
+            ("abcdefghijk", "zz", "zzcHgwjUppv8U"),
+            ("a", "..", "..4/MD05HLWjI"),
+        ] {
+            let salt = salt.as_bytes().try_into().unwrap();
+            assert_eq!(crypt(password.as_bytes(), salt).unwrap(), hash);
+        }
+
+        // only the first 8 bytes are used
+        assert_eq!(
+            crypt(b"abcdefgh", *b"zz").unwrap(),
+            crypt(b"abcdefghijk", *b"zz").unwrap()
+        );
+
+        assert!(matches!(
+            crypt(b"hello", *b"a!"),
+            Err(Error::InvalidSalt(_))
+        ));
+        assert!(matches!(
+            crypt(b"hel\0lo", *b"ab"),
+            Err(Error::InvalidPassword(_))
+        ));
+    }
+
+    #[test]
+    fn test_django_auth_crypt() {
+        let encoded = "crypt$$abprj99vCww4M";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = CryptHasher;
+        assert_eq!(hasher.encode("l\u{e8}tmein", "ab").unwrap(), encoded);
+        for salt in ["", "a", "abc", "a$"] {
+            assert!(hasher.encode("l\u{e8}tmein", salt).is_err(), "{salt}");
+        }
+
+        let parts = hasher.split(encoded).unwrap();
+        assert_eq!(parts.salt, "ab");
+        assert_eq!(parts.hash, "prj99vCww4M");
+
+        for encoded in [
+            "crypt$abprj99vCww4M",
+            "crypt$$abprj99vCww4",
+            "crypt$$abprj99vCww4MM",
+            "crypt$$abprj99vCww\u{e8}",
+        ] {
+            assert!(django_auth("l\u{e8}tmein", encoded).is_err(), "{encoded}");
+        }
+    }
+}
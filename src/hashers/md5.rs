@@ -0,0 +1,176 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use md5::{Digest, Md5};
+use zeroize::Zeroizing;
+
+use super::{check_salt, constant_time_hex_eq, hex, EncodedParts, Hasher};
+use crate::{Error, Result};
+
+/// Django's `MD5PasswordHasher`:
+///
+/// `md5$<salt>$<hexdigest>`
+///
+/// **Insecure**, only useful to migrate passwords from old Django versions
+/// (requires the `insecure-hashers` feature).
+#[derive(Debug, Clone, Default)]
+pub struct Md5Hasher;
+
+impl Hasher for Md5Hasher {
+    fn algorithm(&self) -> &str {
+        "md5"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        check_salt(salt)?;
+        let hash = hex(&salted_digest(salt, password));
+        Ok(format!("{}${}${}", self.algorithm(), salt, hash))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let parts = self.split(encoded)?;
+        Ok(constant_time_hex_eq(
+            parts.hash,
+            &salted_digest(parts.salt, password),
+        ))
+    }
+}
+
+/// md5(salt + password)
+fn salted_digest(salt: &str, password: &[u8]) -> md5::digest::Output<Md5> {
+    let salted = Zeroizing::new([salt.as_bytes(), password].concat());
+    Md5::digest(salted.as_slice())
+}
+
+/// Django's `UnsaltedMD5PasswordHasher`, a bare 32 characters hex MD5 digest
+/// (or `md5$$<hexdigest>`) without any algorithm prefix.
+///
+/// **Insecure**, only useful to migrate passwords from very old Django
+/// versions (requires the `insecure-hashers` feature).
+#[derive(Debug, Clone, Default)]
+pub struct UnsaltedMd5Hasher;
+
+impl Hasher for UnsaltedMd5Hasher {
+    fn algorithm(&self) -> &str {
+        "unsalted_md5"
+    }
+
+    /// `salt` has to be empty.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        if !salt.is_empty() {
+            return Err(Error::InvalidSalt("unsalted_md5 takes no salt".into()));
+        }
+        Ok(hex(&Md5::digest(password)))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let parts = self.split(encoded)?;
+        Ok(constant_time_hex_eq(parts.hash, &Md5::digest(password)))
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        if !is_unsalted_md5(encoded) {
+            return Err(Error::InvalidEncodedPassword(
+                "not an unsalted_md5 encoded password".to_owned(),
+            ));
+        }
+        Ok(EncodedParts {
+            params: Vec::new(),
+            salt: "",
+            hash: encoded.strip_prefix("md5$$").unwrap_or(encoded),
+        })
+    }
+}
+
+/// Check if `encoded` looks like an unsalted MD5 digest, following Django's
+/// `identify_hasher`.
+pub(crate) fn is_unsalted_md5(encoded: &str) -> bool {
+    let digest = encoded.strip_prefix("md5$$").unwrap_or(encoded);
+    digest.len() == 32 && digest.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_django_auth_md5() {
+        // "md5$seasalt$" + hashlib.md5(b"seasaltl\xc3\xa8tmein").hexdigest()
+        let encoded = "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = Md5Hasher;
+        assert_eq!(hasher.encode("l\u{e8}tmein", "seasalt").unwrap(), encoded);
+        let encoded = hasher.encode("hello", "btQDcwXF2RoK6Q").unwrap();
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+
+        assert!(hasher.encode("l\u{e8}tmein", "sea$salt").is_err());
+        assert!(hasher.verify("l\u{e8}tmein", "md5$seasalt").is_err());
+    }
+
+    #[test]
+    fn test_django_auth_unsalted_md5() {
+        // hashlib.md5(b"l\xc3\xa8tmein").hexdigest()
+        let encoded = "88a434c88cca4e900f7874cd98123f43";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let encoded = "md5$$88a434c88cca4e900f7874cd98123f43";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = UnsaltedMd5Hasher;
+        assert_eq!(
+            hasher.encode("l\u{e8}tmein", "").unwrap(),
+            "88a434c88cca4e900f7874cd98123f43"
+        );
+        assert!(hasher.encode("l\u{e8}tmein", "salt").is_err());
+
+        // not 32 hex characters
+        assert!(django_auth("l\u{e8}tmein", "88a434c88cca4e900f7874cd98123f4").is_err());
+        assert!(django_auth("l\u{e8}tmein", "88a434c88cca4e900f7874cd98123f4x").is_err());
+    }
+
+    #[test]
+    fn test_md5_split() {
+        let parts = Md5Hasher
+            .split("md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3")
+            .unwrap();
+        assert!(parts.params.is_empty());
+        assert_eq!(parts.salt, "seasalt");
+        assert_eq!(parts.hash, "3f86d0d3d465b7b458c231bf3555c0e3");
+        assert!(Md5Hasher.split("md5$seasalt").is_err());
+        assert!(Md5Hasher.split("md5$sea$salt$hash").is_err());
+
+        for encoded in [
+            "88a434c88cca4e900f7874cd98123f43",
+            "md5$$88a434c88cca4e900f7874cd98123f43",
+        ] {
+            let parts = UnsaltedMd5Hasher.split(encoded).unwrap();
+            assert_eq!(parts.salt, "");
+            assert_eq!(parts.hash, "88a434c88cca4e900f7874cd98123f43");
+        }
+        assert!(UnsaltedMd5Hasher.split("md5$$88a434c8").is_err());
+    }
+}
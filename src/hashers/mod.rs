@@ -0,0 +1,222 @@
+//! Django's password hashers.
+
+#[cfg(feature = "argon2")]
+pub(crate) mod argon2;
+#[cfg(feature = "bcrypt")]
+pub(crate) mod bcrypt;
+#[cfg(feature = "insecure-hashers")]
+pub(crate) mod crypt;
+#[cfg(feature = "insecure-hashers")]
+pub(crate) mod md5;
+pub(crate) mod pbkdf2;
+#[cfg(feature = "scrypt")]
+pub(crate) mod scrypt;
+#[cfg(feature = "insecure-hashers")]
+pub(crate) mod sha1;
+pub(crate) mod wrapped;
+
+#[cfg(feature = "argon2")]
+pub use self::argon2::Argon2Hasher;
+#[cfg(feature = "bcrypt")]
+pub use self::bcrypt::{BcryptHasher, BcryptSha256Hasher};
+#[cfg(feature = "insecure-hashers")]
+pub use self::crypt::CryptHasher;
+#[cfg(feature = "insecure-hashers")]
+pub use self::md5::{Md5Hasher, UnsaltedMd5Hasher};
+#[cfg(feature = "legacy-sha1")]
+pub use self::pbkdf2::Pbkdf2Sha1Hasher;
+pub use self::pbkdf2::{
+    Pbkdf2Sha256B64SaltHasher, Pbkdf2Sha256Hasher, Pbkdf2Sha256HexHasher, Pbkdf2Sha512Hasher,
+};
+#[cfg(feature = "scrypt")]
+pub use self::scrypt::ScryptHasher;
+#[cfg(feature = "insecure-hashers")]
+pub use self::sha1::{Sha1Hasher, UnsaltedSha1Hasher};
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Error, Result};
+
+/// A password hashing algorithm, like Django's `BasePasswordHasher`.
+///
+/// Implement this trait to support algorithms which are not built into this
+/// crate.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let hasher = Pbkdf2Sha256Hasher { iterations: 1000 };
+/// let encoded_password = hasher.encode("hello", "seasalt").expect("encode error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$1000$seasalt$"));
+/// assert!(hasher.verify("hello", &encoded_password).expect("verify error"));
+/// ```
+///
+pub trait Hasher {
+    /// The algorithm name, i.e. the prefix (before the first `$`) of the
+    /// passwords encoded by this hasher, e.g. "pbkdf2_sha256".
+    fn algorithm(&self) -> &str;
+
+    /// Encode `password` with `salt` into a Django-style encoded password.
+    fn encode(&self, password: &str, salt: &str) -> Result<String>;
+
+    /// Verify `password` against `encoded`, which should have been produced
+    /// by this hasher.
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool>;
+
+    /// Same as [Hasher::encode] for a password which may not be valid UTF-8.
+    ///
+    /// The default implementation rejects non UTF-8 passwords, the builtin
+    /// hashers accept any bytes.
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        self.encode(utf8_password(password)?, salt)
+    }
+
+    /// Same as [Hasher::verify] for a password which may not be valid UTF-8.
+    ///
+    /// The default implementation rejects non UTF-8 passwords, the builtin
+    /// hashers accept any bytes.
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        self.verify(utf8_password(password)?, encoded)
+    }
+
+    /// Split `encoded`, which should have been produced by this hasher, into
+    /// its components, without decoding the salt and the hash.
+    ///
+    /// Each algorithm has its own layout, the default implementation expects
+    /// `<algorithm>$<salt>$<hash>` (like md5 and sha1).
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        let parts: Vec<&str> = strip_algorithm(self.algorithm(), encoded)?
+            .split('$')
+            .collect();
+        let [salt, hash] = parts[..] else {
+            return Err(Error::InvalidEncodedPassword(format!(
+                "{} encoded password should have 3 components separated by '$'",
+                self.algorithm()
+            )));
+        };
+        Ok(EncodedParts {
+            params: Vec::new(),
+            salt,
+            hash,
+        })
+    }
+
+    /// Whether `encoded`, which should have been produced by this hasher, was
+    /// encoded with weaker parameters (e.g. fewer iterations) than this
+    /// hasher's, like Django's `BasePasswordHasher.must_update`.
+    ///
+    /// The default implementation never asks for an update.
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        let _ = encoded;
+        Ok(false)
+    }
+}
+
+/// The components of an encoded password, see [Hasher::split].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EncodedParts<'a> {
+    /// The algorithm parameters, in their encoded order, e.g. the iterations
+    /// for pbkdf2 or `[N, r, p]` for scrypt.
+    pub params: Vec<&'a str>,
+    /// The salt, as stored (empty for unsalted algorithms).
+    pub salt: &'a str,
+    /// The hash, as encoded by the algorithm.
+    pub hash: &'a str,
+}
+
+fn utf8_password(password: &[u8]) -> Result<&str> {
+    core::str::from_utf8(password)
+        .map_err(|_| Error::InvalidPassword("password is not valid UTF-8".into()))
+}
+
+/// Strip the `algorithm$` prefix from `encoded`.
+fn strip_algorithm<'a>(algorithm: &str, encoded: &'a str) -> Result<&'a str> {
+    encoded
+        .strip_prefix(algorithm)
+        .and_then(|rest| rest.strip_prefix('$'))
+        .ok_or_else(|| Error::InvalidEncodedPassword(format!("not a {algorithm} encoded password")))
+}
+
+/// Reject salts containing the `$` separator.
+fn check_salt(salt: &str) -> Result<()> {
+    if salt.contains('$') {
+        return Err(Error::InvalidSalt("salt contains dollar sign ($)".into()));
+    }
+    Ok(())
+}
+
+/// Reject encoded passwords costing more than [crate::max_memory_cost] KiB,
+/// before hashing.
+#[cfg(any(feature = "argon2", feature = "scrypt"))]
+fn check_memory_cost(algorithm: &str, kib: u64) -> Result<()> {
+    let max = crate::max_memory_cost();
+    if kib > u64::from(max) {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} cost should not be more than {max} KiB, got {kib} KiB"
+        )));
+    }
+    Ok(())
+}
+
+/// Compare the hex digest `a`, as stored by the hex based hashers, to the raw
+/// digest `b` in constant time. Like Django, which compares the encoded
+/// passwords, only lowercase hex matches.
+#[cfg(feature = "insecure-hashers")]
+pub(crate) fn constant_time_hex_eq(a: &str, b: &[u8]) -> bool {
+    crate::constant_time_eq(a.as_bytes(), hex(b).as_bytes())
+}
+
+/// Encode `bytes` as lowercase hex.
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(hex(b""), "");
+        assert_eq!(hex(b"\x00\x0f\xf0\xff"), "000ff0ff");
+    }
+
+    #[cfg(feature = "insecure-hashers")]
+    #[test]
+    fn test_constant_time_hex_eq() {
+        let digest = b"\x88\xa4\x34\xc8\x8c\xca\x4e\x90\x0f\x78\x74\xcd\x98\x12\x3f\x43";
+        assert!(constant_time_hex_eq(
+            "88a434c88cca4e900f7874cd98123f43",
+            digest
+        ));
+        assert!(constant_time_hex_eq("", b""));
+
+        // one digit off
+        assert!(!constant_time_hex_eq(
+            "88a434c88cca4e900f7874cd98123f44",
+            digest
+        ));
+        // uppercase
+        assert!(!constant_time_hex_eq(
+            "88A434C88CCA4E900F7874CD98123F43",
+            digest
+        ));
+        // truncated or too long
+        assert!(!constant_time_hex_eq(
+            "88a434c88cca4e900f7874cd98123f4",
+            digest
+        ));
+        assert!(!constant_time_hex_eq(
+            "88a434c88cca4e900f7874cd98123f4300",
+            digest
+        ));
+        assert!(!constant_time_hex_eq("", digest));
+        // not hex
+        assert!(!constant_time_hex_eq("zz", b"\xff"));
+    }
+}
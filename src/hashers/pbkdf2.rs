@@ -0,0 +1,987 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use base64::prelude::*;
+use pbkdf2::pbkdf2_hmac;
+#[cfg(feature = "legacy-sha1")]
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use zeroize::Zeroizing;
+
+use super::{check_salt, hex, strip_algorithm, EncodedParts, Hasher};
+use crate::{
+    constant_time_eq, default_iterations, format_encoded, max_encode_iterations, max_iterations,
+    Error, Result,
+};
+
+/// Django's `PBKDF2PasswordHasher`, the default hasher:
+///
+/// `pbkdf2_sha256$<iterations>$<salt>$<hash>`
+#[derive(Debug, Clone)]
+pub struct Pbkdf2Sha256Hasher {
+    /// Number of iterations used when encoding, 0 means the default, see
+    /// [default_iterations].
+    pub iterations: u32,
+}
+
+impl Default for Pbkdf2Sha256Hasher {
+    fn default() -> Self {
+        Self {
+            iterations: default_iterations(),
+        }
+    }
+}
+
+impl Hasher for Pbkdf2Sha256Hasher {
+    fn algorithm(&self) -> &str {
+        "pbkdf2_sha256"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        encode(self.algorithm(), password, salt, self.iterations)
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        verify(password, encoded)
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        must_update(encoded, self.iterations)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        encoded_parts(encoded)
+    }
+}
+
+/// Django's `PBKDF2SHA1PasswordHasher`:
+///
+/// `pbkdf2_sha1$<iterations>$<salt>$<hash>`
+#[cfg(feature = "legacy-sha1")]
+#[derive(Debug, Clone)]
+pub struct Pbkdf2Sha1Hasher {
+    /// Number of iterations used when encoding, 0 means the default, see
+    /// [default_iterations].
+    pub iterations: u32,
+}
+
+#[cfg(feature = "legacy-sha1")]
+impl Default for Pbkdf2Sha1Hasher {
+    fn default() -> Self {
+        Self {
+            iterations: default_iterations(),
+        }
+    }
+}
+
+#[cfg(feature = "legacy-sha1")]
+impl Hasher for Pbkdf2Sha1Hasher {
+    fn algorithm(&self) -> &str {
+        "pbkdf2_sha1"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        encode(self.algorithm(), password, salt, self.iterations)
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        verify(password, encoded)
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        must_update(encoded, self.iterations)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        encoded_parts(encoded)
+    }
+}
+
+/// pbkdf2 with HMAC-SHA512, used by some Django forks and custom hashers:
+///
+/// `pbkdf2_sha512$<iterations>$<salt>$<hash>`
+///
+/// The hash is the 64 bytes digest, base64 encoded. Django doesn't ship this
+/// hasher.
+#[derive(Debug, Clone)]
+pub struct Pbkdf2Sha512Hasher {
+    /// Number of iterations used when encoding, 0 means the default, see
+    /// [default_iterations].
+    pub iterations: u32,
+}
+
+impl Default for Pbkdf2Sha512Hasher {
+    fn default() -> Self {
+        Self {
+            iterations: default_iterations(),
+        }
+    }
+}
+
+impl Hasher for Pbkdf2Sha512Hasher {
+    fn algorithm(&self) -> &str {
+        "pbkdf2_sha512"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        encode(self.algorithm(), password, salt, self.iterations)
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        verify(password, encoded)
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        must_update(encoded, self.iterations)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        encoded_parts(encoded)
+    }
+}
+
+/// pbkdf2_sha256 with the hash stored as lowercase hex instead of base64, for
+/// consumers which expect hex digests:
+///
+/// `pbkdf2_sha256_hex$<iterations>$<salt>$<hexdigest>`
+///
+/// The distinct algorithm name keeps these encoded passwords from being
+/// mistaken for Django's, but **Django can't verify them**: only use this for
+/// storages which are not shared with Django.
+#[derive(Debug, Clone)]
+pub struct Pbkdf2Sha256HexHasher {
+    /// Number of iterations used when encoding, 0 means the default, see
+    /// [default_iterations].
+    pub iterations: u32,
+}
+
+impl Default for Pbkdf2Sha256HexHasher {
+    fn default() -> Self {
+        Self {
+            iterations: default_iterations(),
+        }
+    }
+}
+
+impl Hasher for Pbkdf2Sha256HexHasher {
+    fn algorithm(&self) -> &str {
+        "pbkdf2_sha256_hex"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        check_salt(salt)?;
+        let iterations = if self.iterations == 0 {
+            default_iterations()
+        } else {
+            self.iterations
+        };
+        check_encode_iterations(self.algorithm(), iterations)?;
+        let hash = hash_password("pbkdf2_sha256", password, salt, iterations, 32)?;
+        Ok(format_encoded(
+            self.algorithm(),
+            iterations,
+            salt,
+            &hex(&hash),
+        ))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        let (algorithm, iterations, salt, hash) = split(encoded)?;
+        check_iterations(algorithm, iterations)?;
+        let hash = decode_hex(algorithm, hash)?;
+        check_derived_len("pbkdf2_sha256", hash.len())?;
+        let computed = hash_password("pbkdf2_sha256", password, salt, iterations, hash.len())?;
+        Ok(constant_time_eq(&computed, &hash))
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        must_update(encoded, self.iterations)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        encoded_parts(encoded)
+    }
+}
+
+/// pbkdf2_sha256 with a binary salt, stored base64 encoded, for key derivation
+/// schemes which want more entropy than Django's alphanumeric salts:
+///
+/// `pbkdf2_sha256_b64salt$<iterations>$<base64 salt>$<hash>`
+///
+/// [Hasher::encode] takes the salt bytes as a string, see
+/// [crate::django_encode_password_with_salt_bytes] for any bytes. The
+/// distinct algorithm name keeps these encoded passwords from being mistaken
+/// for Django's, but **Django can't verify them**: only use this for storages
+/// which are not shared with Django.
+#[derive(Debug, Clone)]
+pub struct Pbkdf2Sha256B64SaltHasher {
+    /// Number of iterations used when encoding, 0 means the default, see
+    /// [default_iterations].
+    pub iterations: u32,
+}
+
+impl Default for Pbkdf2Sha256B64SaltHasher {
+    fn default() -> Self {
+        Self {
+            iterations: default_iterations(),
+        }
+    }
+}
+
+impl Pbkdf2Sha256B64SaltHasher {
+    /// Encode `password` with the binary `salt`.
+    pub(crate) fn encode_salt_bytes(&self, password: &[u8], salt: &[u8]) -> Result<String> {
+        let iterations = if self.iterations == 0 {
+            default_iterations()
+        } else {
+            self.iterations
+        };
+        check_encode_iterations(self.algorithm(), iterations)?;
+        let hash = hash_password("pbkdf2_sha256", password, salt, iterations, 32)?;
+        Ok(format_encoded(
+            self.algorithm(),
+            iterations,
+            &BASE64_STANDARD.encode(salt),
+            &BASE64_STANDARD.encode(&hash),
+        ))
+    }
+}
+
+impl Hasher for Pbkdf2Sha256B64SaltHasher {
+    fn algorithm(&self) -> &str {
+        "pbkdf2_sha256_b64salt"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        self.encode_salt_bytes(password, salt.as_bytes())
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        let (algorithm, iterations, salt, hash) = split(encoded)?;
+        let salt = BASE64_STANDARD.decode(salt).map_err(|_| {
+            Error::InvalidEncodedPassword(format!("{algorithm} salt is not valid base64"))
+        })?;
+        check_iterations(algorithm, iterations)?;
+        let hash = decode_hash(algorithm, hash)?;
+        check_derived_len("pbkdf2_sha256", hash.len())?;
+        let computed = hash_password("pbkdf2_sha256", password, salt, iterations, hash.len())?;
+        Ok(constant_time_eq(&computed, &hash))
+    }
+
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        must_update(encoded, self.iterations)
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        strip_algorithm(self.algorithm(), encoded)?;
+        encoded_parts(encoded)
+    }
+}
+
+/// Whether `encoded_password` uses fewer iterations than `iterations` (0 means
+/// the default).
+fn must_update(encoded_password: &str, iterations: u32) -> Result<bool> {
+    let (_, stored_iterations, _, _) = split(encoded_password)?;
+    let iterations = if iterations == 0 {
+        default_iterations()
+    } else {
+        iterations
+    };
+    Ok(stored_iterations < iterations)
+}
+
+fn verify(password: &[u8], encoded_password: &str) -> Result<bool> {
+    let (algorithm, iterations, salt, hash) = split(encoded_password)?;
+    verify_hash(algorithm, password, salt, iterations, hash)
+}
+
+/// Verify a pbkdf2 encoded password whose salt was escaped by
+/// [encode_escaped].
+pub(crate) fn verify_escaped(password: &[u8], encoded_password: &str) -> Result<bool> {
+    let (algorithm, iterations, salt, hash) = split(encoded_password)?;
+    let salt = unescape_salt(salt)?;
+    verify_hash(algorithm, password, &salt, iterations, hash)
+}
+
+/// Verify `candidates` against a pbkdf2 encoded password, which is parsed only
+/// once, and return the index of the first matching one.
+pub(crate) fn verify_candidates(
+    candidates: &[&str],
+    encoded_password: &str,
+) -> Result<Option<usize>> {
+    let (algorithm, iterations, salt, hash) = split(encoded_password)?;
+    check_iterations(algorithm, iterations)?;
+    let hash = decode_hash(algorithm, hash)?;
+    check_derived_len(algorithm, hash.len())?;
+
+    for (i, candidate) in candidates.iter().enumerate() {
+        let computed = hash_password(
+            algorithm,
+            candidate.as_bytes(),
+            salt,
+            iterations,
+            hash.len(),
+        )?;
+        if constant_time_eq(&computed, &hash) {
+            return Ok(Some(i));
+        }
+    }
+    Ok(None)
+}
+
+fn verify_hash(
+    algorithm: &str,
+    password: &[u8],
+    salt: &str,
+    iterations: u32,
+    hash: &str,
+) -> Result<bool> {
+    check_iterations(algorithm, iterations)?;
+    let hash = decode_hash(algorithm, hash)?;
+    check_derived_len(algorithm, hash.len())?;
+
+    // the hash may have been derived with a custom length instead of the
+    // digest size, derive as many bytes as stored
+    let computed = hash_password(algorithm, password, salt, iterations, hash.len())?;
+    Ok(constant_time_eq(&computed, &hash))
+}
+
+pub(crate) fn check_iterations(algorithm: &str, iterations: u32) -> Result<()> {
+    let max = max_iterations();
+    if iterations > max {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} iterations should not be more than {max}, got {iterations}"
+        )));
+    }
+    Ok(())
+}
+
+/// The shortest stored hash which is verified, shorter ones would match too
+/// many passwords.
+const MIN_HASH_LEN: usize = 16;
+
+/// Check that a stored hash of `len` bytes can be verified: custom lengths are
+/// derived as stored, but only from [MIN_HASH_LEN] up to the digest size, so
+/// that truncated hashes don't match other passwords and oversized ones don't
+/// multiply the cost of a verification.
+fn check_derived_len(algorithm: &str, len: usize) -> Result<()> {
+    let max = hash_len(algorithm)?;
+    if !(MIN_HASH_LEN..=max).contains(&len) {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} hash should be {MIN_HASH_LEN} to {max} bytes, got {len}"
+        )));
+    }
+    Ok(())
+}
+
+/// Reject encoding with more than [max_encode_iterations].
+pub(crate) fn check_encode_iterations(algorithm: &str, iterations: u32) -> Result<()> {
+    let max = max_encode_iterations();
+    if iterations > max {
+        return Err(Error::InvalidIterations(format!(
+            "{algorithm} iterations should not be more than {max}, got {iterations}"
+        )));
+    }
+    Ok(())
+}
+
+/// Check that the hash of a pbkdf2 encoded password has the digest size of its
+/// algorithm, i.e. was encoded by Django.
+pub(crate) fn check_hash_len(encoded_password: &str) -> Result<()> {
+    let (algorithm, _, _, hash) = split(encoded_password)?;
+    let (hash, expected) = match algorithm {
+        "pbkdf2_sha256_hex" => (decode_hex(algorithm, hash)?, hash_len("pbkdf2_sha256")?),
+        algorithm => (decode_hash(algorithm, hash)?, hash_len(algorithm)?),
+    };
+    if hash.len() != expected {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} hash should be {expected} bytes, got {}",
+            hash.len()
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn decode_hash(algorithm: &str, hash: &str) -> Result<Vec<u8>> {
+    // a stored hash which can't be produced by the algorithm means the
+    // encoded password is corrupted, rather than the password being wrong.
+    // Django encodes with the standard alphabet, but some tools emit URL-safe
+    // base64
+    let hash = BASE64_STANDARD
+        .decode(hash)
+        .or_else(|_| BASE64_URL_SAFE.decode(hash))
+        .map_err(|_| {
+            Error::InvalidEncodedPassword(format!("{algorithm} hash is not valid base64"))
+        })?;
+    if hash.is_empty() {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} hash should not be empty"
+        )));
+    }
+    Ok(hash)
+}
+
+/// Decode a lowercase hex hash, as encoded by [Pbkdf2Sha256HexHasher].
+fn decode_hex(algorithm: &str, hash: &str) -> Result<Vec<u8>> {
+    let digit = |b: u8| match b {
+        b'0'..=b'9' => Some(b - b'0'),
+        b'a'..=b'f' => Some(b - b'a' + 10),
+        _ => None,
+    };
+    let invalid = || Error::InvalidEncodedPassword(format!("{algorithm} hash is not valid hex"));
+    if !hash.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+    let hash = hash
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| Some(digit(pair[0])? << 4 | digit(pair[1])?))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(invalid)?;
+    if hash.is_empty() {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} hash should not be empty"
+        )));
+    }
+    Ok(hash)
+}
+
+/// [split] into [EncodedParts], the iterations being the only parameter.
+fn encoded_parts(encoded_password: &str) -> Result<EncodedParts<'_>> {
+    // validates the layout and the iterations
+    let (_, _, salt, hash) = split(encoded_password)?;
+    let iterations = encoded_password.split('$').nth(1).unwrap_or_default();
+    Ok(EncodedParts {
+        params: vec![iterations],
+        salt,
+        hash,
+    })
+}
+
+/// Split a pbkdf2 encoded password into algorithm, iterations, salt and hash.
+pub(crate) fn split(encoded_password: &str) -> Result<(&str, u32, &str, &str)> {
+    // split hashed_password into 4 parts: algorithm, iterations, salt, hash
+    let parts = encoded_password.splitn(4, '$');
+
+    let parts: Vec<&str> = parts.collect();
+    if parts.len() != 4 {
+        return Err(Error::InvalidEncodedPassword(
+            "encoded password should have 4 components separated by '$'".to_owned(),
+        ));
+    }
+
+    let (algorithm, iterations, salt, hash) = (parts[0], parts[1], parts[2], parts[3]);
+
+    let iterations = parse_iterations(iterations)?;
+    // unlike when encoding, 0 doesn't mean the default: Django never stores it
+    if iterations == 0 {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} iterations should not be 0"
+        )));
+    }
+
+    Ok((algorithm, iterations, salt, hash))
+}
+
+/// Parse the iterations component of an encoded password, which should only
+/// have ASCII digits: unlike `str::parse`, a leading `+` is rejected.
+pub(crate) fn parse_iterations(iterations: &str) -> Result<u32> {
+    if iterations.is_empty() || !iterations.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "invalid iterations: {iterations:?}"
+        )));
+    }
+    // only digits, so it can only overflow
+    iterations.parse().map_err(|_| {
+        Error::InvalidEncodedPassword(format!(
+            "iterations should not be more than {}, got {iterations}",
+            u32::MAX
+        ))
+    })
+}
+
+fn encode(algorithm: &str, password: &[u8], salt: &str, mut iterations: u32) -> Result<String> {
+    check_salt(salt)?;
+
+    if iterations == 0 {
+        iterations = default_iterations();
+    }
+    check_encode_iterations(algorithm, iterations)?;
+
+    let hash = hash_password(algorithm, password, salt, iterations, hash_len(algorithm)?)?;
+    let hash = BASE64_STANDARD.encode(&hash);
+    Ok(format_encoded(algorithm, iterations, salt, &hash))
+}
+
+/// Same as [encode], but hash with `salt` as is and store it percent-encoded
+/// (`$` as `%24`, `%` as `%25`), so salts may contain the separator.
+pub(crate) fn encode_escaped(
+    algorithm: &str,
+    password: &[u8],
+    salt: &str,
+    mut iterations: u32,
+) -> Result<String> {
+    if iterations == 0 {
+        iterations = default_iterations();
+    }
+    check_encode_iterations(algorithm, iterations)?;
+
+    let hash = hash_password(algorithm, password, salt, iterations, hash_len(algorithm)?)?;
+    let hash = BASE64_STANDARD.encode(&hash);
+    Ok(format_encoded(
+        algorithm,
+        iterations,
+        &escape_salt(salt),
+        &hash,
+    ))
+}
+
+fn escape_salt(salt: &str) -> String {
+    salt.replace('%', "%25").replace('$', "%24")
+}
+
+fn unescape_salt(salt: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(salt.len());
+    let mut rest = salt;
+    while let Some(i) = rest.find('%') {
+        unescaped.push_str(&rest[..i]);
+        let c = match rest.get(i + 1..i + 3) {
+            Some("24") => '$',
+            Some("25") => '%',
+            _ => {
+                return Err(Error::InvalidEncodedPassword(
+                    "salt should only contain %24 or %25 escapes".to_owned(),
+                ))
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[i + 3..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+pub(crate) fn hash_password(
+    algorithm: &str,
+    password: &[u8],
+    salt: impl AsRef<[u8]>,
+    iterations: u32,
+    len: usize,
+) -> Result<Zeroizing<Vec<u8>>> {
+    let (p, s) = (password, salt.as_ref());
+    let mut hash = Zeroizing::new(vec![0u8; len]);
+    match algorithm {
+        "pbkdf2_sha256" => pbkdf2_hmac::<Sha256>(p, s, iterations, &mut hash),
+        #[cfg(feature = "legacy-sha1")]
+        "pbkdf2_sha1" => pbkdf2_hmac::<Sha1>(p, s, iterations, &mut hash),
+        "pbkdf2_sha512" => pbkdf2_hmac::<Sha512>(p, s, iterations, &mut hash),
+        _ => return Err(Error::UnsupportedAlgorithm(algorithm.to_owned())),
+    }
+
+    Ok(hash)
+}
+
+/// The length of a pbkdf2 (or [Pbkdf2Sha256HexHasher]) encoded password with
+/// `iterations` and a `salt_len` bytes salt.
+pub(crate) fn encoded_len(algorithm: &str, iterations: u32, salt_len: usize) -> Result<usize> {
+    let (salt_len, hash_len) = match algorithm {
+        "pbkdf2_sha256_hex" => (salt_len, 2 * hash_len("pbkdf2_sha256")?),
+        // the salt is padded base64 too
+        "pbkdf2_sha256_b64salt" => (
+            salt_len.div_ceil(3) * 4,
+            hash_len("pbkdf2_sha256")?.div_ceil(3) * 4,
+        ),
+        // padded base64
+        algorithm => (salt_len, hash_len(algorithm)?.div_ceil(3) * 4),
+    };
+    let digits = iterations.checked_ilog10().unwrap_or_default() as usize + 1;
+    Ok(algorithm.len() + 1 + digits + 1 + salt_len + 1 + hash_len)
+}
+
+/// The length in bytes of the hashes encoded with `algorithm`, i.e. its digest
+/// size.
+pub(crate) fn hash_len(algorithm: &str) -> Result<usize> {
+    match algorithm {
+        "pbkdf2_sha256" => Ok(32),
+        "pbkdf2_sha1" => Ok(20),
+        "pbkdf2_sha512" => Ok(64),
+        _ => Err(Error::UnsupportedAlgorithm(algorithm.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pbkdf2_sha512_hasher() {
+        let hasher = Pbkdf2Sha512Hasher { iterations: 1000 };
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        // base64.b64encode(hashlib.pbkdf2_hmac("sha512", b"hello", b"seasalt", 1000))
+        assert_eq!(encoded, "pbkdf2_sha512$1000$seasalt$9EmDloRvwcysQnIq3lo8Mzne3FaccLadeTaYarxzxpZNs2P3uy7gMkPw0k60qPWt7i5nMkjW6Ut6jR/sBTnHyQ==");
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+        assert!(crate::django_auth("hello", &encoded).unwrap());
+        check_hash_len(&encoded).unwrap();
+
+        // not a prefix of the sha256 hash
+        let res = hasher.verify(
+            "hello",
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = check_hash_len(
+            "pbkdf2_sha512$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_b64salt_hasher() {
+        let hasher = Pbkdf2Sha256B64SaltHasher { iterations: 1000 };
+        let encoded = hasher
+            .encode_salt_bytes(b"hello", b"\x00\xffsea$salt")
+            .unwrap();
+        // base64.b64encode(hashlib.pbkdf2_hmac("sha256", b"hello", b"\x00\xffsea$salt", 1000))
+        assert_eq!(
+            encoded,
+            "pbkdf2_sha256_b64salt$1000$AP9zZWEkc2FsdA==$S2FBjiUIX9znsKks75B/FxGuTJIJfDsC3YhLFVvUm0M="
+        );
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+        assert!(crate::django_auth("hello", &encoded).unwrap());
+
+        // a string salt is hashed as its bytes
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        assert_eq!(
+            encoded,
+            "pbkdf2_sha256_b64salt$1000$c2Vhc2FsdA==$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI="
+        );
+        assert!(hasher.verify("hello", &encoded).unwrap());
+
+        for salt in ["seasalt", "c2Vhc2FsdA", "!"] {
+            let res = hasher.verify(
+                "hello",
+                &format!("pbkdf2_sha256_b64salt$1000${salt}$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI="),
+            );
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{salt}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256_hex_hasher() {
+        let hasher = Pbkdf2Sha256HexHasher { iterations: 1000 };
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        // hashlib.pbkdf2_hmac("sha256", b"hello", b"seasalt", 1000).hex()
+        assert_eq!(
+            encoded,
+            "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0afb1e91dce39cc197e36c84983d26942"
+        );
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+        assert!(!hasher.must_update(&encoded).unwrap());
+        assert!(Pbkdf2Sha256HexHasher { iterations: 2000 }
+            .must_update(&encoded)
+            .unwrap());
+
+        // the base64 encoded password of the same hash isn't accepted
+        let res = hasher.verify(
+            "hello",
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        // corrupted hashes are errors, not mismatches
+        for hash in ["", "443", "443D822A", "RD2CKhnDcnVNzUV4", "zz"] {
+            let res = hasher.verify("hello", &format!("pbkdf2_sha256_hex$1000$seasalt${hash}"));
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{hash}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_hashers() {
+        let hasher = Pbkdf2Sha256Hasher { iterations: 1000 };
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        assert!(encoded.starts_with("pbkdf2_sha256$1000$seasalt$"));
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+
+        // corrupted hashes are errors, not mismatches
+        let (prefix, _) = encoded.rsplit_once('$').unwrap();
+        for hash in ["not base64!", "aGVsbG8", ""] {
+            let res = hasher.verify("hello", &format!("{prefix}${hash}"));
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        }
+
+        // hashes derived with a custom length, from 16 bytes up to the digest
+        // size (base64.b64encode(hashlib.pbkdf2_hmac("sha256", b"hello", b"seasalt", 1000, 16)))
+        let hasher = Pbkdf2Sha256Hasher { iterations: 1000 };
+        let encoded = "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538A==";
+        assert!(hasher.verify("hello", encoded).unwrap());
+        assert!(!hasher.verify("world", encoded).unwrap());
+        assert_eq!(
+            verify_candidates(&["world", "hello"], encoded).unwrap(),
+            Some(1)
+        );
+        // shorter ones would match too many passwords, longer ones multiply
+        // the cost of a verification
+        for hash in [
+            "RA==",
+            "RD2CKhnDcnVNzUV45453",
+            "RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUKsM9/W9aH/QBc5a83zjwWQhGwf4Rnt3amoCXgRy8KBuA==",
+        ] {
+            let encoded = format!("pbkdf2_sha256$1000$seasalt${hash}");
+            let res = hasher.verify("hello", &encoded);
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))), "{hash}");
+            let res = verify_candidates(&["hello"], &encoded);
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))), "{hash}");
+        }
+        let res = Pbkdf2Sha256HexHasher { iterations: 1000 }
+            .verify("hello", "pbkdf2_sha256_hex$1000$seasalt$44");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        // URL-safe base64 (base64.urlsafe_b64encode)
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        assert_eq!(
+            encoded,
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI="
+        );
+        let encoded = "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K-x6R3OOcwZfjbISYPSaUI=";
+        assert!(hasher.verify("hello", encoded).unwrap());
+        assert!(!hasher.verify("world", encoded).unwrap());
+        check_hash_len(encoded).unwrap();
+        // the alphabets can't be mixed, and padding is still required
+        for hash in [
+            "RD2CKhnDcnVNzUV454538K-x6R3OOcwZfjbISYPSaUI",
+            "RD2CKhnDcnVNzUV454538K-x6R3OOcwZfjbISYP/aUI=",
+        ] {
+            let res = hasher.verify("hello", &format!("pbkdf2_sha256$1000$seasalt${hash}"));
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_must_update() {
+        let hasher = Pbkdf2Sha256Hasher { iterations: 1000 };
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        assert!(!hasher.must_update(&encoded).unwrap());
+        assert!(!Pbkdf2Sha256Hasher { iterations: 999 }
+            .must_update(&encoded)
+            .unwrap());
+        assert!(Pbkdf2Sha256Hasher { iterations: 1001 }
+            .must_update(&encoded)
+            .unwrap());
+        assert!(Pbkdf2Sha256Hasher { iterations: 0 }
+            .must_update(&encoded)
+            .unwrap());
+
+        assert!(hasher.must_update("pbkdf2_sha256$x$seasalt$hash").is_err());
+        assert!(hasher.must_update("pbkdf2_sha1$1000$seasalt$hash").is_err());
+    }
+
+    #[test]
+    fn test_pbkdf2_split() {
+        let hasher = Pbkdf2Sha256Hasher::default();
+        let encoded =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        let parts = hasher.split(encoded).unwrap();
+        assert_eq!(parts.params, ["180000"]);
+        assert_eq!(parts.salt, "btQDcwXF2RoK6Q");
+        assert_eq!(parts.hash, "D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=");
+        assert_eq!(
+            split(encoded).unwrap(),
+            ("pbkdf2_sha256", 180000, parts.salt, parts.hash)
+        );
+
+        // like split, everything after the salt is the hash
+        let parts = hasher.split("pbkdf2_sha256$1000$salt$ha$sh").unwrap();
+        assert_eq!(parts.hash, "ha$sh");
+
+        for encoded in [
+            "pbkdf2_sha256$1000$salt",
+            "pbkdf2_sha256$abc$salt$hash",
+            "pbkdf2_sha1$1000$salt$hash",
+        ] {
+            assert!(hasher.split(encoded).is_err(), "{encoded}");
+        }
+    }
+
+    #[test]
+    fn test_parse_iterations() {
+        assert_eq!(parse_iterations("180000").unwrap(), 180000);
+        assert_eq!(parse_iterations("0180000").unwrap(), 180000);
+        assert_eq!(parse_iterations("4294967295").unwrap(), u32::MAX);
+
+        for (iterations, message) in [
+            ("+180000", "invalid iterations: \"+180000\""),
+            (" 180000", "invalid iterations: \" 180000\""),
+            ("180000\n", "invalid iterations: \"180000\\n\""),
+            ("-1", "invalid iterations: \"-1\""),
+            ("1e5", "invalid iterations: \"1e5\""),
+            ("١٨٠٠٠٠", "invalid iterations: \"١٨٠٠٠٠\""),
+            ("", "invalid iterations: \"\""),
+            (
+                "99999999999",
+                "iterations should not be more than 4294967295, got 99999999999",
+            ),
+            (
+                "4294967296",
+                "iterations should not be more than 4294967295, got 4294967296",
+            ),
+        ] {
+            assert_eq!(
+                parse_iterations(iterations),
+                Err(Error::InvalidEncodedPassword(message.to_owned()))
+            );
+        }
+
+        let hasher = Pbkdf2Sha256Hasher::default();
+        for encoded in [
+            "pbkdf2_sha256$+180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha256$ 180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha256$99999999999$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        ] {
+            let res = hasher.verify("hello", encoded);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_zero_iterations() {
+        // hashlib.pbkdf2_hmac rejects 0 iterations, Django never stores them
+        let encoded = "pbkdf2_sha256$0$seasalt$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        let hasher = Pbkdf2Sha256Hasher::default();
+        assert_eq!(
+            hasher.verify("hello", encoded),
+            Err(Error::InvalidEncodedPassword(
+                "pbkdf2_sha256 iterations should not be 0".to_owned()
+            ))
+        );
+        assert!(hasher.must_update(encoded).is_err());
+        assert!(hasher.split(encoded).is_err());
+
+        // while 0 means the default when encoding
+        let encoded = Pbkdf2Sha256Hasher { iterations: 0 }
+            .encode("hello", "seasalt")
+            .unwrap();
+        let (_, iterations, _, _) = split(&encoded).unwrap();
+        assert_eq!(iterations, default_iterations());
+    }
+
+    #[test]
+    fn test_escaped_salt() {
+        for salt in ["seasalt", "sea$salt", "100%$", "%24", "$$", ""] {
+            let escaped = escape_salt(salt);
+            assert!(!escaped.contains('$'));
+            assert_eq!(unescape_salt(&escaped).unwrap(), salt);
+        }
+        assert_eq!(escape_salt("sea$salt"), "sea%24salt");
+        assert_eq!(escape_salt("100%$"), "100%25%24");
+
+        for salt in ["%", "sea%2", "%2x", "%20"] {
+            assert!(unescape_salt(salt).is_err(), "{salt}");
+        }
+
+        // (base64.b64encode(hashlib.pbkdf2_hmac("sha256", b"hello", b"sea$salt", 1000)))
+        let encoded = encode_escaped("pbkdf2_sha256", b"hello", "sea$salt", 1000).unwrap();
+        assert_eq!(
+            encoded,
+            "pbkdf2_sha256$1000$sea%24salt$wYBp/0V9mQ2jITgG6PPT4Kie70eITLAPnkEwGP8t0MY="
+        );
+        assert!(verify_escaped(b"hello", &encoded).unwrap());
+        assert!(!verify_escaped(b"world", &encoded).unwrap());
+        // the salt is not unescaped by the regular verification
+        assert!(!verify(b"hello", &encoded).unwrap());
+    }
+
+    #[cfg(feature = "legacy-sha1")]
+    #[test]
+    fn test_pbkdf2_sha1_hasher() {
+        let hasher = Pbkdf2Sha1Hasher { iterations: 1000 };
+        let encoded = hasher.encode("hello", "seasalt").unwrap();
+        assert!(encoded.starts_with("pbkdf2_sha1$1000$seasalt$"));
+        assert!(hasher.verify("hello", &encoded).unwrap());
+
+        // a hash derived with a custom length, up to the 20 bytes digest size
+        let encoded = "pbkdf2_sha1$1000$seasalt$P/GroD9L/lXBd94ogw8aIQ==";
+        assert!(Pbkdf2Sha1Hasher::default()
+            .verify("hello", encoded)
+            .unwrap());
+        let res = Pbkdf2Sha1Hasher::default().verify(
+            "hello",
+            "pbkdf2_sha1$1000$seasalt$P/GroD9L/lXBd94ogw8aIZW+5t7L7FHE708oOtYbAec=",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        // hashers only verify their own algorithm
+        assert!(Pbkdf2Sha256Hasher::default()
+            .verify("hello", encoded)
+            .is_err());
+    }
+}
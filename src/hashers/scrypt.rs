@@ -0,0 +1,295 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use base64::prelude::*;
+use zeroize::Zeroizing;
+
+use super::{check_memory_cost, check_salt, strip_algorithm, EncodedParts, Hasher};
+use crate::{constant_time_eq, Error, Result};
+
+/// Django's `ScryptPasswordHasher`:
+///
+/// `scrypt$<work_factor>$<salt>$<block_size>$<parallelism>$<hash>`
+#[derive(Debug, Clone)]
+pub struct ScryptHasher {
+    /// CPU/memory cost (N), must be a power of 2.
+    pub work_factor: u32,
+    /// Block size (r).
+    pub block_size: u32,
+    /// Degree of parallelism (p).
+    pub parallelism: u32,
+}
+
+impl Default for ScryptHasher {
+    /// Django's default parameters.
+    fn default() -> Self {
+        Self {
+            work_factor: 1 << 14,
+            block_size: 8,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Hasher for ScryptHasher {
+    fn algorithm(&self) -> &str {
+        "scrypt"
+    }
+
+    /// Encode `password` with a 64 bytes hash, like Django does.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        check_salt(salt)?;
+
+        let hash = hash_password(
+            password,
+            salt,
+            self.work_factor,
+            self.block_size,
+            self.parallelism,
+            64,
+        )?;
+
+        Ok(format!(
+            "{}${}${}${}${}${}",
+            self.algorithm(),
+            self.work_factor,
+            salt,
+            self.block_size,
+            self.parallelism,
+            BASE64_STANDARD.encode(&hash),
+        ))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let decoded = decode(&self.split(encoded)?)?;
+        // 128 * N * r bytes, filled once for each of the p blocks
+        let kib = u128::from(decoded.work_factor)
+            * u128::from(decoded.block_size)
+            * u128::from(decoded.parallelism)
+            / 8;
+        check_memory_cost(self.algorithm(), u64::try_from(kib).unwrap_or(u64::MAX))?;
+
+        let computed = hash_password(
+            password,
+            decoded.salt,
+            decoded.work_factor,
+            decoded.block_size,
+            decoded.parallelism,
+            decoded.hash.len(),
+        )?;
+
+        Ok(constant_time_eq(&computed, &decoded.hash))
+    }
+
+    /// `scrypt$<work factor>$<salt>$<block size>$<parallelism>$<hash>`, note
+    /// the salt between the parameters.
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        let parts: Vec<&str> = strip_algorithm(self.algorithm(), encoded)?
+            .split('$')
+            .collect();
+        let [work_factor, salt, block_size, parallelism, hash] = parts[..] else {
+            return Err(Error::InvalidEncodedPassword(
+                "scrypt encoded password should have 6 components separated by '$'".to_owned(),
+            ));
+        };
+        Ok(EncodedParts {
+            params: vec![work_factor, block_size, parallelism],
+            salt,
+            hash,
+        })
+    }
+
+    /// Encoded passwords with a lower work factor, block size or parallelism
+    /// need an update.
+    fn must_update(&self, encoded: &str) -> Result<bool> {
+        let decoded = decode(&self.split(encoded)?)?;
+        Ok(decoded.work_factor < self.work_factor
+            || decoded.block_size < self.block_size
+            || decoded.parallelism < self.parallelism)
+    }
+}
+
+/// The components of a scrypt encoded password.
+struct Decoded<'a> {
+    work_factor: u32,
+    salt: &'a str,
+    block_size: u32,
+    parallelism: u32,
+    hash: Vec<u8>,
+}
+
+/// Parse the components of a scrypt encoded password.
+fn decode<'a>(parts: &EncodedParts<'a>) -> Result<Decoded<'a>> {
+    let [work_factor, block_size, parallelism] = parts.params[..] else {
+        return Err(Error::InvalidEncodedPassword(
+            "scrypt encoded password should have 6 components separated by '$'".to_owned(),
+        ));
+    };
+
+    let parse = |name: &str, value: &str| -> Result<u32> {
+        value
+            .parse()
+            .map_err(|_| Error::InvalidEncodedPassword(format!("invalid scrypt {name}: {value}")))
+    };
+
+    let hash = BASE64_STANDARD
+        .decode(parts.hash)
+        .map_err(|_| Error::InvalidEncodedPassword("scrypt hash is not valid base64".to_owned()))?;
+
+    Ok(Decoded {
+        work_factor: parse("work factor", work_factor)?,
+        salt: parts.salt,
+        block_size: parse("block size", block_size)?,
+        parallelism: parse("parallelism", parallelism)?,
+        hash,
+    })
+}
+
+fn hash_password(
+    password: &[u8],
+    salt: &str,
+    work_factor: u32,
+    block_size: u32,
+    parallelism: u32,
+    len: usize,
+) -> Result<Zeroizing<Vec<u8>>> {
+    // the scrypt crate takes log2(N), N has to be a power of 2 anyway
+    if work_factor < 2 || !work_factor.is_power_of_two() {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "scrypt work factor should be a power of 2: {work_factor}"
+        )));
+    }
+    let log_n = work_factor.trailing_zeros() as u8;
+
+    let params = scrypt::Params::new(log_n, block_size, parallelism, len)
+        .map_err(|e| Error::InvalidEncodedPassword(format!("invalid scrypt parameters: {e}")))?;
+
+    let mut hash = Zeroizing::new(vec![0u8; len]);
+    scrypt::scrypt(password, salt.as_bytes(), &params, &mut hash)
+        .map_err(|e| Error::InvalidEncodedPassword(format!("scrypt error: {e}")))?;
+
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_django_auth_scrypt() {
+        // hashlib.scrypt("lètmein", salt="seasalt", n=1024, r=8, p=1, dklen=64)
+        let encoded = "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96ldSJKs4U0WQif8UbWlfO3wJ\
+                       DNhKOg+pPtDckiT6Zw0qkEvKIQ1MdONfGxsWrpoiNg==";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        // work factor is not a power of 2
+        let res = django_auth(
+            "l\u{e8}tmein",
+            "scrypt$1000$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+        );
+        assert!(res.is_err());
+
+        // pbkdf2-style layout with 4 components
+        let res = django_auth(
+            "l\u{e8}tmein",
+            "scrypt$1024$seasalt$+qO2jTkVUbPNlniTkHY96g==",
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_scrypt_max_memory_cost() {
+        // rejected before allocating terabytes or hashing for hours
+        for params in [
+            "2147483648$seasalt$8$1",
+            "1024$seasalt$4294967295$1",
+            "1024$seasalt$8$4294967295",
+            "2147483648$seasalt$4294967295$4294967295",
+        ] {
+            let res = django_auth(
+                "l\u{e8}tmein",
+                format!("scrypt${params}$+qO2jTkVUbPNlniTkHY96g=="),
+            );
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{params}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_scrypt_encode() {
+        let hasher = ScryptHasher {
+            work_factor: 1024,
+            ..Default::default()
+        };
+        let encoded = hasher.encode("l\u{e8}tmein", "seasalt").unwrap();
+        assert_eq!(
+            encoded,
+            "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96ldSJKs4U0WQif8UbWlfO3wJ\
+             DNhKOg+pPtDckiT6Zw0qkEvKIQ1MdONfGxsWrpoiNg=="
+        );
+        assert!(hasher.verify("l\u{e8}tmein", &encoded).unwrap());
+        assert!(!hasher.verify("letmein", &encoded).unwrap());
+
+        assert!(hasher.encode("l\u{e8}tmein", "sea$salt").is_err());
+    }
+
+    #[test]
+    fn test_scrypt_must_update() {
+        let encoded = "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==";
+        let hasher = ScryptHasher {
+            work_factor: 1024,
+            ..Default::default()
+        };
+        assert!(!hasher.must_update(encoded).unwrap());
+        assert!(ScryptHasher::default().must_update(encoded).unwrap());
+        assert!(ScryptHasher {
+            parallelism: 2,
+            ..hasher.clone()
+        }
+        .must_update(encoded)
+        .unwrap());
+        assert!(!ScryptHasher {
+            block_size: 4,
+            ..hasher.clone()
+        }
+        .must_update(encoded)
+        .unwrap());
+
+        assert!(hasher
+            .must_update("scrypt$1024$seasalt$+qO2jTkVUbPNlniTkHY96g==")
+            .is_err());
+    }
+
+    #[test]
+    fn test_scrypt_split() {
+        let parts = ScryptHasher::default()
+            .split("scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==")
+            .unwrap();
+        assert_eq!(parts.params, ["1024", "8", "1"]);
+        assert_eq!(parts.salt, "seasalt");
+        assert_eq!(parts.hash, "+qO2jTkVUbPNlniTkHY96g==");
+
+        for encoded in [
+            "scrypt$1024$seasalt$+qO2jTkVUbPNlniTkHY96g==",
+            "scrypt$1024$sea$salt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+            "pbkdf2_sha256$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+        ] {
+            assert!(ScryptHasher::default().split(encoded).is_err(), "{encoded}");
+        }
+    }
+}
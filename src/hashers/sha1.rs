@@ -0,0 +1,163 @@
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use sha1::{Digest, Sha1};
+use zeroize::Zeroizing;
+
+use super::{check_salt, constant_time_hex_eq, hex, EncodedParts, Hasher};
+use crate::{Error, Result};
+
+/// Django's `SHA1PasswordHasher`:
+///
+/// `sha1$<salt>$<hexdigest>`
+///
+/// **Insecure**, only useful to migrate passwords from old Django versions
+/// (requires the `insecure-hashers` feature).
+#[derive(Debug, Clone, Default)]
+pub struct Sha1Hasher;
+
+impl Hasher for Sha1Hasher {
+    fn algorithm(&self) -> &str {
+        "sha1"
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        check_salt(salt)?;
+        let hash = hex(&salted_digest(salt, password));
+        Ok(format!("{}${}${}", self.algorithm(), salt, hash))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let parts = self.split(encoded)?;
+        Ok(constant_time_hex_eq(
+            parts.hash,
+            &salted_digest(parts.salt, password),
+        ))
+    }
+}
+
+/// sha1(salt + password)
+fn salted_digest(salt: &str, password: &[u8]) -> sha1::digest::Output<Sha1> {
+    let salted = Zeroizing::new([salt.as_bytes(), password].concat());
+    Sha1::digest(salted.as_slice())
+}
+
+/// Django's `UnsaltedSHA1PasswordHasher`:
+///
+/// `sha1$$<hexdigest>`
+///
+/// **Insecure**, only useful to migrate passwords from very old Django
+/// versions (requires the `insecure-hashers` feature).
+#[derive(Debug, Clone, Default)]
+pub struct UnsaltedSha1Hasher;
+
+impl Hasher for UnsaltedSha1Hasher {
+    fn algorithm(&self) -> &str {
+        "unsalted_sha1"
+    }
+
+    /// `salt` has to be empty.
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, password: &[u8], salt: &str) -> Result<String> {
+        if !salt.is_empty() {
+            return Err(Error::InvalidSalt("unsalted_sha1 takes no salt".into()));
+        }
+        let hash = hex(&Sha1::digest(password));
+        Ok(format!("sha1$${hash}"))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let parts = self.split(encoded)?;
+        Ok(constant_time_hex_eq(parts.hash, &Sha1::digest(password)))
+    }
+
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        // note the empty salt component
+        let hash = encoded.strip_prefix("sha1$$").ok_or_else(|| {
+            Error::InvalidEncodedPassword("not an unsalted_sha1 encoded password".to_owned())
+        })?;
+        Ok(EncodedParts {
+            params: Vec::new(),
+            salt: "",
+            hash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_django_auth_sha1() {
+        // "sha1$seasalt$" + hashlib.sha1(b"seasaltl\xc3\xa8tmein").hexdigest()
+        let encoded = "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = Sha1Hasher;
+        assert_eq!(hasher.encode("l\u{e8}tmein", "seasalt").unwrap(), encoded);
+        let encoded = hasher.encode("hello", "btQDcwXF2RoK6Q").unwrap();
+        assert!(hasher.verify("hello", &encoded).unwrap());
+        assert!(!hasher.verify("world", &encoded).unwrap());
+
+        assert!(hasher.encode("l\u{e8}tmein", "sea$salt").is_err());
+        assert!(hasher.verify("l\u{e8}tmein", "sha1$seasalt").is_err());
+    }
+
+    #[test]
+    fn test_django_auth_unsalted_sha1() {
+        // "sha1$$" + hashlib.sha1(b"l\xc3\xa8tmein").hexdigest()
+        let encoded = "sha1$$6d138ca3ae545631b3abd71a4f076ce759c5700b";
+        assert!(django_auth("l\u{e8}tmein", encoded).unwrap());
+        assert!(!django_auth("letmein", encoded).unwrap());
+
+        let hasher = UnsaltedSha1Hasher;
+        assert_eq!(hasher.encode("l\u{e8}tmein", "").unwrap(), encoded);
+        assert!(hasher.encode("l\u{e8}tmein", "salt").is_err());
+        assert!(hasher
+            .verify(
+                "l\u{e8}tmein",
+                "sha1$6d138ca3ae545631b3abd71a4f076ce759c5700b"
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_sha1_split() {
+        let parts = Sha1Hasher
+            .split("sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8")
+            .unwrap();
+        assert!(parts.params.is_empty());
+        assert_eq!(parts.salt, "seasalt");
+        assert_eq!(parts.hash, "cff36ea83f5706ce9aa7454e63e431fc726b2dc8");
+        assert!(Sha1Hasher.split("sha1$seasalt").is_err());
+
+        let parts = UnsaltedSha1Hasher
+            .split("sha1$$6d138ca3ae545631b3abd71a4f076ce759c5700b")
+            .unwrap();
+        assert_eq!(parts.salt, "");
+        assert_eq!(parts.hash, "6d138ca3ae545631b3abd71a4f076ce759c5700b");
+        assert!(UnsaltedSha1Hasher
+            .split("sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8")
+            .is_err());
+    }
+}
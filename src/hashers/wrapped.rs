@@ -0,0 +1,274 @@
+//! Onion hashing: pbkdf2 encoded passwords wrapped in more pbkdf2_sha256
+//! rounds, without the plaintext password, see [crate::wrap_hash].
+
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use base64::prelude::*;
+use zeroize::Zeroizing;
+
+use super::pbkdf2::{
+    check_encode_iterations, check_hash_len, check_iterations, decode_hash, hash_len,
+    hash_password, parse_iterations,
+};
+use super::{strip_algorithm, EncodedParts, Hasher};
+use crate::{constant_time_eq, default_iterations, identify_hasher, Error, Result};
+
+const ALGORITHM: &str = "pbkdf2_wrapped";
+
+/// Verifies the `pbkdf2_wrapped` encoded passwords produced by
+/// [crate::wrap_hash], see its documentation for the format.
+///
+/// It can't encode passwords, only wrap encoded ones.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Pbkdf2WrappedHasher;
+
+impl Hasher for Pbkdf2WrappedHasher {
+    fn algorithm(&self) -> &str {
+        ALGORITHM
+    }
+
+    fn encode(&self, password: &str, salt: &str) -> Result<String> {
+        self.encode_bytes(password.as_bytes(), salt)
+    }
+
+    fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+        self.verify_bytes(password.as_bytes(), encoded)
+    }
+
+    fn encode_bytes(&self, _password: &[u8], _salt: &str) -> Result<String> {
+        Err(Error::UnsupportedAlgorithm(format!(
+            "{ALGORITHM} can't encode passwords, wrap an encoded password instead"
+        )))
+    }
+
+    fn verify_bytes(&self, password: &[u8], encoded: &str) -> Result<bool> {
+        let wrapped = parse(encoded)?;
+
+        // the inner encoded password, then each layer in order
+        let mut computed = hash_password(
+            wrapped.algorithm,
+            password,
+            wrapped.salt,
+            wrapped.iterations,
+            hash_len(wrapped.algorithm)?,
+        )?;
+        for &(iterations, salt) in &wrapped.layers {
+            computed = hash_password("pbkdf2_sha256", &computed, salt, iterations, 32)?;
+        }
+        Ok(constant_time_eq(&computed, &wrapped.hash))
+    }
+
+    /// The params are the inner algorithm and iterations, followed by the
+    /// iterations and salt of each layer, the salt is the inner one.
+    fn split<'a>(&self, encoded: &'a str) -> Result<EncodedParts<'a>> {
+        parse(encoded)?;
+        let mut params: Vec<&str> = strip_algorithm(ALGORITHM, encoded)?.split('$').collect();
+        let hash = params.pop().unwrap_or_default();
+        let salt = params.remove(2);
+        Ok(EncodedParts { params, salt, hash })
+    }
+}
+
+/// A parsed `pbkdf2_wrapped` encoded password.
+struct Wrapped<'a> {
+    /// The inner encoded password's algorithm, iterations and salt.
+    algorithm: &'a str,
+    iterations: u32,
+    salt: &'a str,
+    /// The iterations and salt of each layer, innermost first.
+    layers: Vec<(u32, &'a str)>,
+    hash: Vec<u8>,
+}
+
+fn parse(encoded: &str) -> Result<Wrapped<'_>> {
+    let parts: Vec<&str> = strip_algorithm(ALGORITHM, encoded)?.split('$').collect();
+    if parts.len() < 6 || !parts.len().is_multiple_of(2) {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{ALGORITHM} encoded password should have an algorithm, iterations, a salt, \
+             iterations and salt pairs and a hash separated by '$'"
+        )));
+    }
+
+    let algorithm = parts[0];
+    check_inner_algorithm(algorithm)?;
+    let iterations = parse_layer_iterations(parts[1])?;
+    let layers = parts[3..parts.len() - 1]
+        .chunks(2)
+        .map(|layer| Ok((parse_layer_iterations(layer[0])?, layer[1])))
+        .collect::<Result<Vec<_>>>()?;
+
+    // each verification runs all of them
+    let total = layers
+        .iter()
+        .try_fold(iterations, |total, &(iterations, _)| {
+            total.checked_add(iterations)
+        })
+        .unwrap_or(u32::MAX);
+    check_iterations(ALGORITHM, total)?;
+
+    let hash = decode_hash(ALGORITHM, parts[parts.len() - 1])?;
+    Ok(Wrapped {
+        algorithm,
+        iterations,
+        salt: parts[2],
+        layers,
+        hash,
+    })
+}
+
+/// Check that the inner encoded password's `algorithm` can be verified.
+fn check_inner_algorithm(algorithm: &str) -> Result<()> {
+    match algorithm {
+        "pbkdf2_sha256" | "pbkdf2_sha512" => Ok(()),
+        #[cfg(feature = "legacy-sha1")]
+        "pbkdf2_sha1" => Ok(()),
+        #[cfg(not(feature = "legacy-sha1"))]
+        "pbkdf2_sha1" => Err(crate::Algorithm::Pbkdf2Sha1.feature_not_enabled()),
+        _ => Err(Error::UnsupportedAlgorithm(algorithm.to_owned())),
+    }
+}
+
+fn parse_layer_iterations(iterations: &str) -> Result<u32> {
+    match parse_iterations(iterations)? {
+        0 => Err(Error::InvalidEncodedPassword(format!(
+            "{ALGORITHM} iterations should not be 0"
+        ))),
+        iterations => Ok(iterations),
+    }
+}
+
+/// Wrap a pbkdf2 or `pbkdf2_wrapped` `encoded_password` in another layer, see
+/// [crate::wrap_hash].
+pub(crate) fn wrap(encoded_password: &str, salt: &str, iterations: u32) -> Result<String> {
+    let iterations = if iterations == 0 {
+        default_iterations()
+    } else {
+        iterations
+    };
+    check_encode_iterations(ALGORITHM, iterations)?;
+
+    let prefix = match identify_hasher(encoded_password)? {
+        ALGORITHM => {
+            parse(encoded_password)?;
+            ""
+        }
+        algorithm => {
+            check_inner_algorithm(algorithm)?;
+            // the inner hash is recomputed with the digest size when verifying
+            check_hash_len(encoded_password)?;
+            "pbkdf2_wrapped$"
+        }
+    };
+
+    // validated above
+    let (inner, hash) = encoded_password.rsplit_once('$').unwrap_or_default();
+    let hash = Zeroizing::new(decode_hash(ALGORITHM, hash)?);
+    let hash = hash_password("pbkdf2_sha256", &hash, salt, iterations, 32)?;
+    Ok(format!(
+        "{prefix}{inner}${iterations}${salt}${}",
+        BASE64_STANDARD.encode(&hash)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::django_auth;
+
+    #[test]
+    fn test_wrap() {
+        let encoded = "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=";
+
+        // base64.b64encode(hashlib.pbkdf2_hmac("sha256", <inner hash>, b"pepper", 1000))
+        let wrapped = wrap(encoded, "pepper", 1000).unwrap();
+        assert_eq!(
+            wrapped,
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$\
+             LWChDx0rILwcEjewSZK7lj3iOKdzMNlcQ4JzPCPrWNo="
+        );
+        assert!(django_auth("hello", &wrapped).unwrap());
+        assert!(!django_auth("world", &wrapped).unwrap());
+
+        // wrapped again
+        let wrapped = wrap(&wrapped, "onion", 2000).unwrap();
+        assert_eq!(
+            wrapped,
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$2000$onion$\
+             ndHLy7fTvJiIT/oY9Dk6/tUBPdsTT33hNGtK+ZJJyNk="
+        );
+        assert!(django_auth("hello", &wrapped).unwrap());
+        assert!(!django_auth("world", &wrapped).unwrap());
+
+        let parts = Pbkdf2WrappedHasher.split(&wrapped).unwrap();
+        assert_eq!(
+            parts.params,
+            ["pbkdf2_sha256", "1000", "1000", "pepper", "2000", "onion"]
+        );
+        assert_eq!(parts.salt, "seasalt");
+        assert_eq!(parts.hash, "ndHLy7fTvJiIT/oY9Dk6/tUBPdsTT33hNGtK+ZJJyNk=");
+    }
+
+    #[test]
+    fn test_wrap_errors() {
+        // custom length inner hashes can't be verified once wrapped
+        let res = wrap(
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538A==",
+            "pepper",
+            1000,
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = wrap(
+            "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3",
+            "pepper",
+            1000,
+        );
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+
+        for encoded in [
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$hash",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$hash",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$0$pepper$aGVsbG8=",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$x$pepper$aGVsbG8=",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$4294967295$pepper$aGVsbG8=",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$!",
+        ] {
+            let res = django_auth("hello", encoded);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded}"
+            );
+        }
+        let res = django_auth(
+            "hello",
+            "pbkdf2_wrapped$md5$1000$seasalt$1000$pepper$aGVsbG8=",
+        );
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+
+        let res = Pbkdf2WrappedHasher.encode("hello", "seasalt");
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_wrap_pbkdf2_sha1() {
+        // base64.b64encode(hashlib.pbkdf2_hmac("sha1", b"hello", b"seasalt", 1000))
+        let encoded = "pbkdf2_sha1$1000$seasalt$P/GroD9L/lXBd94ogw8aIZW+5t4=";
+        let res = wrap(encoded, "pepper", 1000);
+        if cfg!(feature = "legacy-sha1") {
+            let wrapped = res.unwrap();
+            assert!(wrapped.starts_with("pbkdf2_wrapped$pbkdf2_sha1$1000$seasalt$1000$pepper$"));
+            assert!(django_auth("hello", &wrapped).unwrap());
+            assert!(!django_auth("world", &wrapped).unwrap());
+        } else {
+            // it couldn't be verified
+            assert!(matches!(res, Err(Error::FeatureNotEnabled(_))));
+            let res = django_auth(
+                "hello",
+                "pbkdf2_wrapped$pbkdf2_sha1$1000$seasalt$1000$pepper$aGVsbG8=",
+            );
+            assert!(matches!(res, Err(Error::FeatureNotEnabled(_))));
+        }
+    }
+}
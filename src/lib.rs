@@ -1,25 +1,168 @@
-use base64::prelude::*;
-use pbkdf2::pbkdf2_hmac_array;
-use sha2::Sha256;
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-type Result<T> = std::result::Result<T, Error>;
+extern crate alloc;
 
-#[derive(thiserror::Error, Debug)]
+use alloc::borrow::ToOwned;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
+
+use rand::distributions::Alphanumeric;
+use rand::{Rng, RngCore};
+use subtle::ConstantTimeEq;
+
+// Log events with the `log` feature, without it the arguments are only
+// type-checked, never evaluated. Never log passwords or hashes.
+macro_rules! log_debug {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::debug!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+macro_rules! log_error {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::error!($($arg)+);
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+mod algorithm;
+mod audit;
+mod builder;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod encoded;
+mod hashers;
+mod password;
+#[cfg(feature = "pepper")]
+mod pepper;
+mod registry;
+mod stream;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use algorithm::Algorithm;
+pub use audit::{audit, AuditReport};
+pub use builder::Pbkdf2Sha256;
+pub use encoded::EncodedPassword;
+#[cfg(feature = "argon2")]
+pub use hashers::Argon2Hasher;
+#[cfg(feature = "legacy-sha1")]
+pub use hashers::Pbkdf2Sha1Hasher;
+#[cfg(feature = "scrypt")]
+pub use hashers::ScryptHasher;
+#[cfg(feature = "bcrypt")]
+pub use hashers::{BcryptHasher, BcryptSha256Hasher};
+#[cfg(feature = "insecure-hashers")]
+pub use hashers::{CryptHasher, Md5Hasher, Sha1Hasher, UnsaltedMd5Hasher, UnsaltedSha1Hasher};
+pub use hashers::{
+    EncodedParts, Hasher, Pbkdf2Sha256B64SaltHasher, Pbkdf2Sha256Hasher, Pbkdf2Sha256HexHasher,
+    Pbkdf2Sha512Hasher,
+};
+pub use password::Password;
+#[cfg(feature = "pepper")]
+pub use pepper::{django_auth_with_pepper, django_encode_password_with_pepper};
+pub use registry::HasherRegistry;
+pub use stream::StreamingVerifier;
+
+pub type Result<T> = core::result::Result<T, Error>;
+
+/// The errors returned by this crate.
+///
+/// New variants may be added in minor releases, so a `match` on an `Error`
+/// needs a wildcard arm:
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// match django_auth("hello", "foo$salt$hash") {
+///     Ok(res) => println!("verified: {res}"),
+///     Err(Error::InvalidEncodedPassword(e)) => println!("corrupted hash: {e}"),
+///     Err(e) => println!("other error: {e}"),
+/// }
+/// ```
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Error {
-    #[error("invalid django-style encoded password: {0}")]
     InvalidEncodedPassword(String),
-
-    #[error("unsupported algorithm: {0}")]
     UnsupportedAlgorithm(String),
-
-    #[error("invalid salt: {0}")]
     InvalidSalt(String),
+    InvalidPassword(String),
+    AlgorithmNotAllowed(String),
+    IncorrectPassword,
+    /// The algorithm is known, but its crate feature isn't enabled, see
+    /// [Algorithm::feature].
+    FeatureNotEnabled(String),
+    /// The iterations to encode with are above the safety ceiling, see
+    /// [max_encode_iterations].
+    InvalidIterations(String),
+    /// The requested key length is out of range, see [derive_key].
+    InvalidLength(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidEncodedPassword(e) => {
+                write!(f, "invalid django-style encoded password: {e}")
+            }
+            Error::UnsupportedAlgorithm(e) => write!(f, "unsupported algorithm: {e}"),
+            Error::InvalidSalt(e) => write!(f, "invalid salt: {e}"),
+            Error::InvalidPassword(e) => write!(f, "invalid password: {e}"),
+            Error::AlgorithmNotAllowed(e) => write!(f, "algorithm not allowed: {e}"),
+            Error::IncorrectPassword => write!(f, "incorrect password"),
+            Error::FeatureNotEnabled(e) => write!(f, "feature not enabled: {e}"),
+            Error::InvalidIterations(e) => write!(f, "invalid iterations: {e}"),
+            Error::InvalidLength(e) => write!(f, "invalid length: {e}"),
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
 /// Verify `password` based on `encoded_password` which is managed by Django,
 /// return Ok(true) if verification is successful, otherwise return false.
 ///
-/// Currently only the default pbkdf2_sha256 algorithm is supported.
+/// An error is returned when `encoded_password` is malformed, e.g. when its
+/// hash is empty or isn't valid (standard or URL-safe) base64, so that a
+/// corrupted database row can be told apart from a wrong password, or when
+/// `password` is longer than [max_password_length]. Unusable passwords (see
+/// [is_password_usable]) never match. Surrounding ASCII whitespace in
+/// `encoded_password`, e.g. a trailing newline from a database export, is
+/// ignored.
+///
+/// Currently the pbkdf2_sha256, pbkdf2_sha1, argon2, bcrypt_sha256, bcrypt and
+/// scrypt algorithms are supported, pbkdf2_sha256 always and the others when
+/// their crate feature is enabled, as well as pbkdf2_sha512 (see
+/// [Pbkdf2Sha512Hasher]) used by some Django forks. Use a [HasherRegistry] to
+/// restrict the accepted algorithms or add custom ones.
+///
+/// The computed hash is compared to the stored one in constant time (like
+/// Django's `constant_time_compare`), so the time taken doesn't leak how many
+/// leading bytes matched.
 ///
 /// # Usage
 ///
@@ -34,122 +177,3491 @@ pub enum Error {
 /// assert!(res);
 /// ```
 ///
-pub fn django_auth(password: &str, encoded_password: &str) -> Result<bool> {
-    // split hashed_password into 4 parts: algorithm, iterations, salt, hash
-    let parts = encoded_password.split('$');
+pub fn django_auth(password: impl AsRef<str>, encoded_password: impl AsRef<str>) -> Result<bool> {
+    django_auth_bytes(password.as_ref().as_bytes(), encoded_password)
+}
 
-    let parts: Vec<&str> = parts.take(4).collect();
-    if parts.len() != 4 {
-        return Err(Error::InvalidEncodedPassword(
-            "encoded password should have 4 components separated by '$'".to_owned(),
-        ));
+/// Verify `password` against `encoded_password` like [django_auth], and also
+/// return how long the verification took, e.g. to pick an iteration count
+/// which fits a login latency budget on the target hardware.
+///
+/// This is a capacity planning tool: **don't** make authentication decisions
+/// based on the duration (e.g. rejecting fast verifications), timings are
+/// noisy and reacting to them could leak information about the stored hashes.
+///
+/// Not available on wasm32, where `std::time::Instant` panics.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let (res, elapsed) = django_auth_timed(
+///     "hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("django_auth_timed error");
+///
+/// assert!(res);
+/// println!("180000 iterations took {elapsed:?}");
+/// ```
+///
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn django_auth_timed(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+) -> Result<(bool, std::time::Duration)> {
+    let start = std::time::Instant::now();
+    let res = django_auth(password, encoded_password)?;
+    Ok((res, start.elapsed()))
+}
+
+/// The outcome of [verify].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verification {
+    /// The password matches.
+    Valid,
+    /// The password doesn't match.
+    Invalid,
+    /// The password matches, but the encoded password should be re-encoded
+    /// with [make_password] (or [django_encode_password]), see [verify].
+    ValidButNeedsRehash,
+    /// The encoded password is unusable, see [is_password_usable].
+    UnusablePassword,
+}
+
+impl Verification {
+    /// Return true if the password matches, whether it needs a rehash or not.
+    pub fn is_valid(&self) -> bool {
+        matches!(
+            self,
+            Verification::Valid | Verification::ValidButNeedsRehash
+        )
     }
+}
 
-    let (algorithm, iterations, salt) = (parts[0], parts[1], parts[2]);
+/// Verify `password` against `encoded_password` like [django_auth], and tell
+/// whether the encoded password should be upgraded, like Django's
+/// `check_password` (with its `setter`) does.
+///
+/// A matching encoded password needs a rehash when it doesn't use the
+/// preferred pbkdf2_sha256 algorithm, or uses fewer iterations than
+/// [default_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let res = verify("hello", encoded_password).expect("verify error");
+/// assert_eq!(res, Verification::Valid);
+///
+/// let res = verify("world", encoded_password).expect("verify error");
+/// assert_eq!(res, Verification::Invalid);
+/// ```
+///
+pub fn verify(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+) -> Result<Verification> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref();
+    check_password_length(password.as_bytes())?;
+    let encoded_password = encoded_password.trim_ascii();
+    if !is_password_usable(encoded_password) {
+        return Ok(Verification::UnusablePassword);
+    }
 
-    if algorithm != "pbkdf2_sha256" {
-        return Err(Error::UnsupportedAlgorithm(algorithm.to_owned()));
+    if !django_auth(password, encoded_password)? {
+        return Ok(Verification::Invalid);
     }
 
-    let iterations: u32 = iterations
-        .parse()
-        .expect("invalid iterations in hashed password");
+    if identify_hasher(encoded_password)? != "pbkdf2_sha256"
+        || needs_update(encoded_password, default_iterations())?
+    {
+        Ok(Verification::ValidButNeedsRehash)
+    } else {
+        Ok(Verification::Valid)
+    }
+}
 
-    let encoded = django_encode_password(password, salt, iterations)?;
-    Ok(encoded == encoded_password)
+/// Same as [django_auth] for a password which may not be valid UTF-8, e.g.
+/// one read from a file or a socket.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let res = django_auth_bytes(
+///     b"hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("django_auth_bytes error");
+///
+/// assert!(res);
+/// ```
+///
+pub fn django_auth_bytes(
+    password: impl AsRef<[u8]>,
+    encoded_password: impl AsRef<str>,
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref();
+    check_password_length(password)?;
+    let encoded_password = encoded_password.trim_ascii();
+    let res = HasherRegistry::default().verify_bytes(password, encoded_password);
+    match &res {
+        Ok(true) => log_debug!(
+            "password verified ({})",
+            identify_hasher(encoded_password).unwrap_or_default()
+        ),
+        Ok(false) => {}
+        Err(e) => log_error!("password verification failed: {e}"),
+    }
+    if dummy_hash_on_error() && (res.is_err() || !is_password_usable(encoded_password)) {
+        run_dummy_hash(password);
+    }
+    res
 }
 
-/// Encode `password` in [Django way][1].
+/// Same as [django_auth], but return `false` instead of an error, and take
+/// about the same time whether the encoded password is malformed, of an
+/// unsupported algorithm, unusable, or doesn't match.
+///
+/// When `encoded_password` can't be verified, the password is hashed with
+/// pbkdf2_sha256 and [default_iterations] anyway, like
+/// [set_dummy_hash_on_error] does, but regardless of that setting. The reason
+/// of a failure can't be told, so only use this for endpoints which must not
+/// leak it through their timing.
 ///
 /// # Usage
 ///
 /// ```rust
 /// use django_auth::*;
-/// let password = "hello";
-/// let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 0)
-///     .expect("django_encode_password error");
+///
+/// assert!(django_auth_constant(
+///     "hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ));
+/// // takes about as long as verifying a default pbkdf2_sha256 hash
+/// assert!(!django_auth_constant("hello", "garbage"));
+/// ```
+///
+pub fn django_auth_constant(password: impl AsRef<str>, encoded_password: impl AsRef<str>) -> bool {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    let res = HasherRegistry::default().verify(password, encoded_password);
+    if res.is_err() || !is_password_usable(encoded_password) {
+        run_dummy_hash(password.as_bytes());
+    }
+    res.unwrap_or(false)
+}
 
-/// assert_eq!(
-///     encoded_password,
-///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
-/// );
-/// let res = django_auth(password, &encoded_password).expect("auth failed");
+/// Same as [django_auth], but only accept encoded passwords using one of the
+/// `allowed` algorithms.
+///
+/// Unlike [django_auth], which verifies any supported algorithm, an encoded
+/// password using another algorithm is rejected with
+/// [Error::AlgorithmNotAllowed], even if the password matches, e.g. to force
+/// the users whose passwords are still stored with md5 or sha1 to reset them.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let res = django_auth_with_policy("hello", encoded_password, &["pbkdf2_sha256", "argon2"])
+///     .expect("django_auth_with_policy error");
 /// assert!(res);
-/// ```
 ///
-/// [1]: https://docs.djangoproject.com/en/5.0/topics/auth/passwords/
+/// let res = django_auth_with_policy("hello", encoded_password, &["argon2"]);
+/// assert!(matches!(res, Err(Error::AlgorithmNotAllowed(_))));
+/// ```
 ///
-pub fn django_encode_password(password: &str, salt: &str, mut iterations: u32) -> Result<String> {
-    if salt.contains('$') {
-        return Err(Error::InvalidSalt("salt contains dollar sign ($)".into()));
+pub fn django_auth_with_policy(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+    allowed: &[&str],
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    if is_password_usable(encoded_password) {
+        let algorithm = identify_hasher(encoded_password)?;
+        if !allowed.contains(&algorithm) {
+            return Err(Error::AlgorithmNotAllowed(algorithm.to_owned()));
+        }
     }
+    django_auth(password, encoded_password)
+}
+
+/// Same as [django_auth], but only accept an encoded password using
+/// `expected_algorithm`, so that a tampered entry switching to a weaker
+/// algorithm is rejected with [Error::AlgorithmNotAllowed], even if the
+/// password matches.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let res = django_auth_expect("hello", encoded_password, Algorithm::Pbkdf2Sha256)
+///     .expect("django_auth_expect error");
+/// assert!(res);
+///
+/// let res = django_auth_expect("hello", encoded_password, Algorithm::Argon2);
+/// assert!(matches!(res, Err(Error::AlgorithmNotAllowed(_))));
+/// ```
+///
+pub fn django_auth_expect(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+    expected_algorithm: Algorithm,
+) -> Result<bool> {
+    django_auth_with_policy(password, encoded_password, &[expected_algorithm.as_str()])
+}
 
-    if iterations == 0 {
-        iterations = 180000;
+/// Same as [django_auth], but reject encoded passwords whose salt is shorter
+/// than `min_salt_len`, since short salts indicate a weak or tampered entry.
+///
+/// The salt length is counted in characters as stored in `encoded_password`
+/// (argon2 salts are base64 encoded, bcrypt salts are always 22 characters),
+/// and unsalted algorithms have an empty salt. A too short salt is rejected
+/// with [Error::InvalidSalt], even if the password matches.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let res = django_auth_with_min_salt_len("hello", encoded_password, 12)
+///     .expect("django_auth_with_min_salt_len error");
+/// assert!(res);
+///
+/// let res = django_auth_with_min_salt_len("hello", encoded_password, 16);
+/// assert!(matches!(res, Err(Error::InvalidSalt(_))));
+/// ```
+///
+pub fn django_auth_with_min_salt_len(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+    min_salt_len: usize,
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    if is_password_usable(encoded_password) {
+        let salt = stored_salt(encoded_password)?;
+        if salt.len() < min_salt_len {
+            return Err(Error::InvalidSalt(format!(
+                "salt should be at least {min_salt_len} characters, got {}",
+                salt.len()
+            )));
+        }
     }
+    django_auth(password, encoded_password)
+}
+
+/// Return the salt of `encoded_password`, as stored, split by the hasher of
+/// its algorithm.
+fn stored_salt(encoded_password: &str) -> Result<&str> {
+    let algorithm: Algorithm = identify_hasher(encoded_password)?.parse()?;
+    Ok(algorithm.hasher()?.split(encoded_password)?.salt)
+}
 
-    let hash = pbkdf2_hmac_array::<Sha256, 32>(password.as_bytes(), salt.as_bytes(), iterations);
-    let hash = BASE64_STANDARD.encode(hash);
-    let res = format!("{}${}${}${}", "pbkdf2_sha256", iterations, salt, hash);
+/// Verify a batch of `(password, encoded_password)` pairs, see [django_auth].
+///
+/// The results are in the same order as `pairs`. With the `parallel` feature,
+/// the pairs are verified in parallel on rayon's global thread pool,
+/// otherwise one after the other.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+/// let pairs = vec![
+///     ("hello".to_owned(), encoded_password.to_owned()),
+///     ("world".to_owned(), encoded_password.to_owned()),
+/// ];
+///
+/// let res = django_auth_batch(&pairs);
+/// assert!(res[0].as_ref().expect("django_auth error"));
+/// assert!(!res[1].as_ref().expect("django_auth error"));
+/// ```
+///
+pub fn django_auth_batch(pairs: &[(String, String)]) -> Vec<Result<bool>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
 
-    Ok(res)
+        pairs
+            .par_iter()
+            .map(|(password, encoded_password)| django_auth(password, encoded_password))
+            .collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        django_auth_iter(pairs.iter().map(|(password, encoded)| (password, encoded))).collect()
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Lazily verify `(password, encoded_password)` pairs, see [django_auth]:
+/// each pair is only verified when the result is pulled from the returned
+/// iterator, so millions of rows (e.g. the lines of a CSV read with a
+/// `BufReader`) can be checked with a flat memory usage.
+///
+/// The results are in the same order as `pairs`, and an error only concerns
+/// its own pair, the following ones are still verified. Use
+/// [django_auth_batch] to verify a slice in parallel.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let csv = "alice,hello,pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=
+/// bob,world,pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=";
+///
+/// let pairs = csv.lines().map(|line| {
+///     let mut fields = line.splitn(3, ',').skip(1);
+///     (fields.next().unwrap_or_default(), fields.next().unwrap_or_default())
+/// });
+/// let res: Vec<bool> = django_auth_iter(pairs)
+///     .collect::<Result<_>>()
+///     .expect("django_auth error");
+/// assert_eq!(res, [true, false]);
+/// ```
+///
+pub fn django_auth_iter<P, E>(
+    pairs: impl IntoIterator<Item = (P, E)>,
+) -> impl Iterator<Item = Result<bool>>
+where
+    P: AsRef<str>,
+    E: AsRef<str>,
+{
+    pairs
+        .into_iter()
+        .map(|(password, encoded_password)| django_auth(password, encoded_password))
+}
 
-    #[test]
-    fn test_django_auth() {
-        let res = django_auth(
-            "hello",
-            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
-        )
-        .unwrap();
+/// Verify each of `candidates` against `encoded_password`, e.g. to check if a
+/// user reused a known breached password, and return the index of the first
+/// matching candidate, or `None`.
+///
+/// The encoded password is parsed only once for all the candidates, and the
+/// verification stops at the first match. Unusable passwords (see
+/// [is_password_usable]) match no candidate, and a candidate longer than
+/// [max_password_length] is an error.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let res = verify_candidates(&["123456", "hello", "password"], encoded_password)
+///     .expect("verify_candidates error");
+/// assert_eq!(res, Some(1));
+/// ```
+///
+pub fn verify_candidates(
+    candidates: &[&str],
+    encoded_password: impl AsRef<str>,
+) -> Result<Option<usize>> {
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    for candidate in candidates {
+        check_password_length(candidate.as_bytes())?;
+    }
+    if !is_password_usable(encoded_password) {
+        return Ok(None);
+    }
 
-        assert!(res);
+    let registry = HasherRegistry::default();
+    let hasher = registry.hasher_for(encoded_password)?;
+    // the hex and base64 salt variants encode differently, they go through
+    // their hasher
+    if matches!(
+        identify_hasher(encoded_password)?.parse::<Algorithm>(),
+        Ok(Algorithm::Pbkdf2Sha256 | Algorithm::Pbkdf2Sha1 | Algorithm::Pbkdf2Sha512)
+    ) {
+        return hashers::pbkdf2::verify_candidates(candidates, encoded_password);
+    }
+    for (i, candidate) in candidates.iter().enumerate() {
+        if hasher.verify(candidate, encoded_password)? {
+            return Ok(Some(i));
+        }
+    }
+    Ok(None)
+}
 
-        let res = django_auth(
-            "hello",
-            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU",
-        )
-        .unwrap();
-        assert!(!res);
+/// Same as [django_auth], but runs the hashing on tokio's blocking thread pool
+/// (see `tokio::task::spawn_blocking`), so it doesn't block the async runtime.
+///
+/// Requires the `tokio` feature, and must be called from within a tokio
+/// runtime.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// # tokio::runtime::Runtime::new().unwrap().block_on(async {
+/// let res = django_auth_async(
+///     "hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// )
+/// .await
+/// .expect("django_auth_async error");
+///
+/// assert!(res);
+/// # });
+/// ```
+///
+#[cfg(feature = "tokio")]
+pub async fn django_auth_async(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref();
+    let password = zeroize::Zeroizing::new(password.to_owned());
+    let encoded_password = encoded_password.to_owned();
 
-        let res = django_auth("world", "abc$edf");
-        assert!(res.is_err());
+    let task = tokio::task::spawn_blocking(move || django_auth(&password, &encoded_password));
+    match task.await {
+        Ok(res) => res,
+        // blocking tasks can't be cancelled, so the task panicked
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
     }
+}
 
-    #[test]
-    fn test_djaongo_encode_password() {
-        let password = "hello";
-        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 0)
-            .expect("django_encode_password failed");
-        assert_eq!(
-            encoded_password,
-            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
-        );
-        let res = django_auth(password, &encoded_password).expect("auth failed");
-        assert!(res);
+/// The prefix of Django's unusable passwords, see [is_password_usable].
+pub const UNUSABLE_PASSWORD_PREFIX: &str = "!";
 
-        let password = "hello";
-        let res = django_encode_password(password, "btQDcwXF$2RoK6Q", 0);
-        assert!(res.is_err());
+/// Return false if `encoded_password` is an unusable password, i.e. one set by
+/// Django's `set_unusable_password` (or `make_password(None)`), which starts
+/// with `!` and can't match any password, like Django's `is_password_usable`.
+///
+/// [django_auth] returns `Ok(false)` for unusable passwords.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// assert!(!is_password_usable("!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5"));
+/// assert!(is_password_usable(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ));
+///
+/// let res = django_auth("hello", "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5")
+///     .expect("django_auth error");
+/// assert!(!res);
+/// ```
+///
+pub fn is_password_usable(encoded_password: impl AsRef<str>) -> bool {
+    !encoded_password
+        .as_ref()
+        .starts_with(UNUSABLE_PASSWORD_PREFIX)
+}
 
-        let password = "hello";
-        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 10)
-            .expect("django_encode_password failed");
-        assert_ne!(
-            encoded_password,
-            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
-        );
+/// Same as [django_auth], named after Django's `check_password`.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let res = check_password(
+///     "hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("check_password error");
+///
+/// assert!(res);
+/// ```
+///
+pub fn check_password(password: impl AsRef<str>, encoded: impl AsRef<str>) -> Result<bool> {
+    django_auth(password, encoded)
+}
 
-        let password = "hello";
-        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Qx", 0)
-            .expect("django_encode_password failed");
-        assert_ne!(
-            encoded_password,
-            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
-        );
+/// Return the names of the algorithms which are compiled in (see the crate
+/// features), i.e. which [django_auth] can verify, the preferred one
+/// (pbkdf2_sha256) first.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let supported = supported_algorithms();
+/// assert_eq!(supported[0], "pbkdf2_sha256");
+///
+/// let encoded_password = "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3";
+/// let algorithm = identify_hasher(encoded_password).expect("identify_hasher error");
+/// if !supported.contains(&algorithm) {
+///     println!("{algorithm} passwords can't be verified by this build");
+/// }
+/// ```
+///
+pub fn supported_algorithms() -> Vec<&'static str> {
+    Algorithm::ALL
+        .into_iter()
+        .filter(Algorithm::is_enabled)
+        .map(|algorithm| algorithm.as_str())
+        .collect()
+}
+
+/// Return the name of the algorithm (hasher) used by `encoded_password`,
+/// i.e. the leading component before the first `$`, like Django's
+/// `identify_hasher`.
+///
+/// The returned algorithm is not guaranteed to be supported by this crate.
+///
+/// With the `insecure-hashers` feature, bare 32 characters hex digests (and
+/// `md5$$<hexdigest>`) are identified as "unsalted_md5", like Django does, and
+/// `sha1$$<hexdigest>` as "unsalted_sha1".
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let algorithm = identify_hasher(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("identify_hasher error");
+///
+/// assert_eq!(algorithm, "pbkdf2_sha256");
+/// ```
+///
+pub fn identify_hasher(encoded_password: &str) -> Result<&str> {
+    #[cfg(feature = "insecure-hashers")]
+    if hashers::md5::is_unsalted_md5(encoded_password) {
+        return Ok("unsalted_md5");
+    }
+    #[cfg(feature = "insecure-hashers")]
+    if encoded_password.starts_with("sha1$$") {
+        return Ok("unsalted_sha1");
+    }
+
+    match encoded_password.split_once('$') {
+        Some((algorithm, _)) => Ok(algorithm),
+        None => Err(Error::InvalidEncodedPassword(
+            "encoded password should have components separated by '$'".to_owned(),
+        )),
+    }
+}
+
+/// Check if the encoded passwords `a` and `b` are the same credential, i.e.
+/// share their algorithm, parameters (iterations...), salt and hash, without
+/// the plaintext password and without hashing anything.
+///
+/// Two encodings of the same password with differing salts (or iterations)
+/// always compare unequal, as the password can't be recovered to compare them.
+/// The components are compared in constant time, unusable passwords never
+/// compare equal, and malformed encoded passwords are errors.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let a = django_encode_password("hello", "seasalt", 1000).expect("encode error");
+/// let b = django_encode_password("hello", "seasalt", 1000).expect("encode error");
+/// assert!(encoded_eq(&a, &b).expect("encoded_eq error"));
+///
+/// let c = django_encode_password("hello", "pepper", 1000).expect("encode error");
+/// assert!(!encoded_eq(&a, &c).expect("encoded_eq error"));
+/// ```
+///
+pub fn encoded_eq(a: impl AsRef<str>, b: impl AsRef<str>) -> Result<bool> {
+    let a = a.as_ref().trim_ascii();
+    let b = b.as_ref().trim_ascii();
+    if !is_password_usable(a) || !is_password_usable(b) {
+        return Ok(false);
+    }
+    let algorithm = identify_hasher(a)?;
+    if identify_hasher(b)? != algorithm {
+        return Ok(false);
+    }
+
+    if let "pbkdf2_sha256" | "pbkdf2_sha1" | "pbkdf2_sha512" = algorithm {
+        // compare the iterations as numbers
+        let (_, a_iterations, a_salt, a_hash) = hashers::pbkdf2::split(a)?;
+        let (_, b_iterations, b_salt, b_hash) = hashers::pbkdf2::split(b)?;
+        return Ok((a_iterations == b_iterations)
+            & constant_time_eq(a_salt.as_bytes(), b_salt.as_bytes())
+            & constant_time_eq(a_hash.as_bytes(), b_hash.as_bytes()));
+    }
+
+    let a: Vec<&str> = a.split('$').collect();
+    let b: Vec<&str> = b.split('$').collect();
+    Ok((a.len() == b.len())
+        & a.iter().zip(&b).fold(true, |eq, (a, b)| {
+            eq & constant_time_eq(a.as_bytes(), b.as_bytes())
+        }))
+}
+
+/// Assemble the `<algorithm>$<iterations>$<salt>$<hash>` encoded password
+/// from its components, e.g. to import hashes computed elsewhere, without
+/// hashing anything.
+///
+/// The components are not validated, `hash_b64` is the base64 encoded hash as
+/// stored by Django.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = format_encoded(
+///     "pbkdf2_sha256",
+///     180000,
+///     "btQDcwXF2RoK6Q",
+///     "D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// );
+///
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn format_encoded(algorithm: &str, iterations: u32, salt: &str, hash_b64: &str) -> String {
+    format!("{algorithm}${iterations}${salt}${hash_b64}")
+}
+
+/// Check that the hash of a pbkdf2 (pbkdf2_sha256, pbkdf2_sha1,
+/// pbkdf2_sha512, pbkdf2_sha256_hex) `encoded_password` is valid base64 (hex
+/// for pbkdf2_sha256_hex) and has the digest size of its algorithm (32 bytes
+/// for pbkdf2_sha256 and pbkdf2_sha256_hex, 20 for pbkdf2_sha1, 64 for
+/// pbkdf2_sha512), e.g. to diagnose corrupted imports.
+///
+/// [django_auth] returns [Error::InvalidEncodedPassword] for hashes shorter
+/// than 16 bytes or longer than the digest size, but accepts the lengths in
+/// between (derived with a custom length), use this function to only accept
+/// the hashes encoded by Django. [Error::UnsupportedAlgorithm] is returned for
+/// the other algorithms.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let res = validate_hash_length(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// );
+/// assert!(res.is_ok());
+///
+/// // 30 bytes
+/// let res = validate_hash_length(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e",
+/// );
+/// assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+/// ```
+///
+pub fn validate_hash_length(encoded_password: impl AsRef<str>) -> Result<()> {
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    match identify_hasher(encoded_password)? {
+        "pbkdf2_sha256" | "pbkdf2_sha1" | "pbkdf2_sha512" | "pbkdf2_sha256_hex" => {
+            hashers::pbkdf2::check_hash_len(encoded_password)
+        }
+        algorithm => Err(Error::UnsupportedAlgorithm(algorithm.to_owned())),
+    }
+}
+
+/// Return the length of the passwords encoded with `algorithm`, `iterations`
+/// (0 means [default_iterations]) and a `salt_len` bytes salt, e.g. to size a
+/// database column: the algorithm name, the iterations, the salt and the
+/// base64 (hex for pbkdf2_sha256_hex) encoded hash, separated by `$`.
+///
+/// This is also the maximum length for fewer iterations or shorter salts.
+/// Only the pbkdf2 algorithms (pbkdf2_sha256, pbkdf2_sha1, pbkdf2_sha512,
+/// pbkdf2_sha256_hex and pbkdf2_sha256_b64salt, whose salt takes its base64
+/// length) are supported, the others are rejected with
+/// [Error::UnsupportedAlgorithm].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let len = max_encoded_len("pbkdf2_sha256", 1_000_000, 22).expect("max_encoded_len error");
+/// assert_eq!(len, 89);
+/// ```
+///
+pub fn max_encoded_len(algorithm: &str, iterations: u32, salt_len: usize) -> Result<usize> {
+    let iterations = if iterations == 0 {
+        default_iterations()
+    } else {
+        iterations
+    };
+    match algorithm {
+        "pbkdf2_sha256"
+        | "pbkdf2_sha1"
+        | "pbkdf2_sha512"
+        | "pbkdf2_sha256_hex"
+        | "pbkdf2_sha256_b64salt" => hashers::pbkdf2::encoded_len(algorithm, iterations, salt_len),
+        algorithm => Err(Error::UnsupportedAlgorithm(algorithm.to_owned())),
+    }
+}
+
+/// Return the salt of `encoded_password`, as it was given to the hasher, e.g.
+/// to encode a related credential with the same salt.
+///
+/// The salt is stored as is by most algorithms, argon2 and
+/// pbkdf2_sha256_b64salt store it base64 encoded and it is decoded. bcrypt
+/// salts are generated by bcrypt itself and can't be given to a hasher, so an
+/// error is returned for them, as for malformed encoded passwords.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let salt = get_salt(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("get_salt error");
+///
+/// assert_eq!(salt, "btQDcwXF2RoK6Q");
+/// ```
+///
+pub fn get_salt(encoded_password: impl AsRef<str>) -> Result<String> {
+    use base64::prelude::*;
+
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    let algorithm: Algorithm = identify_hasher(encoded_password)?.parse()?;
+    let salt = algorithm.hasher()?.split(encoded_password)?.salt;
+    match algorithm {
+        Algorithm::Argon2 => BASE64_STANDARD_NO_PAD
+            .decode(salt)
+            .ok()
+            .and_then(|salt| String::from_utf8(salt).ok())
+            .ok_or_else(|| {
+                Error::InvalidEncodedPassword("argon2 salt is not base64 encoded UTF-8".to_owned())
+            }),
+        Algorithm::Pbkdf2Sha256B64Salt => BASE64_STANDARD
+            .decode(salt)
+            .ok()
+            .and_then(|salt| String::from_utf8(salt).ok())
+            .ok_or_else(|| {
+                Error::InvalidEncodedPassword(format!(
+                    "{algorithm} salt is not base64 encoded UTF-8, see \
+                     django_encode_password_with_salt_bytes"
+                ))
+            }),
+        Algorithm::Bcrypt | Algorithm::BcryptSha256 => Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} salts can't be reused"
+        ))),
+        _ => Ok(salt.to_owned()),
+    }
+}
+
+/// Return the number of iterations of a pbkdf2 (pbkdf2_sha256, pbkdf2_sha1,
+/// pbkdf2_sha512, pbkdf2_sha256_hex, pbkdf2_sha256_b64salt)
+/// `encoded_password`.
+///
+/// The other algorithms have no iteration count (argon2, bcrypt and scrypt
+/// have other cost parameters, pbkdf2_wrapped one per layer), an error is
+/// returned for them.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let iterations = get_iterations(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ).expect("get_iterations error");
+///
+/// assert_eq!(iterations, 180000);
+/// ```
+///
+pub fn get_iterations(encoded_password: impl AsRef<str>) -> Result<u32> {
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    let algorithm: Algorithm = identify_hasher(encoded_password)?.parse()?;
+    if !algorithm.has_iterations() {
+        return Err(Error::InvalidEncodedPassword(format!(
+            "{algorithm} encoded passwords have no iterations"
+        )));
+    }
+    let (_, iterations, _, _) = hashers::pbkdf2::split(encoded_password)?;
+    Ok(iterations)
+}
+
+/// Check if `encoded_password` is a well-formed pbkdf2 encoded password, i.e.
+/// one with an iteration count (see [get_iterations]).
+///
+/// `false` is returned for the other algorithms, unusable passwords and
+/// malformed encoded passwords.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// assert!(is_pbkdf2(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// ));
+/// assert!(!is_pbkdf2("bcrypt$$2b$12$4wUO9GRmmK2ZUCZlDtvOTOJV4Sa0mLklIG3z4Bn4"));
+/// assert!(!is_pbkdf2("pbkdf2_sha256$"));
+/// ```
+///
+pub fn is_pbkdf2(encoded_password: impl AsRef<str>) -> bool {
+    get_iterations(encoded_password).is_ok()
+}
+
+/// Check if `encoded_password` should be re-encoded because it uses fewer
+/// iterations than `target_iterations`, like Django's `must_update`.
+///
+/// Only the pbkdf2 algorithms carry an iteration count, `Ok(false)` is
+/// returned for the other supported algorithms, and for unusable passwords (see
+/// [is_password_usable]).
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// assert!(needs_update(encoded_password, 600000).expect("needs_update error"));
+/// assert!(!needs_update(encoded_password, 180000).expect("needs_update error"));
+/// ```
+///
+pub fn needs_update(encoded_password: impl AsRef<str>, target_iterations: u32) -> Result<bool> {
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    if !is_password_usable(encoded_password) {
+        return Ok(false);
+    }
+
+    let algorithm: Algorithm = identify_hasher(encoded_password)?.parse()?;
+    if !algorithm.has_iterations() {
+        return Ok(false);
+    }
+    let iterations = get_iterations(encoded_password)?;
+    let outdated = iterations < target_iterations;
+    if outdated {
+        log_warn!(
+            "{algorithm} encoded password has {iterations} iterations, \
+             fewer than {target_iterations}"
+        );
+    }
+    Ok(outdated)
+}
+
+/// Verify `password` and re-encode it if `encoded_password` uses fewer
+/// iterations than `target_iterations` (0 means [default_iterations]), i.e.
+/// what a login handler does.
+///
+/// Return `Some` new pbkdf2_sha256 encoded password (with a random salt) to
+/// store if an upgrade is needed, `None` if the password matches and
+/// `encoded_password` is up to date (see [needs_update]), and
+/// [Error::IncorrectPassword] if the password doesn't match (or
+/// `encoded_password` is unusable).
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+///
+/// let upgraded = upgrade_if_needed("hello", encoded_password, 200000)
+///     .expect("upgrade_if_needed error")
+///     .expect("no upgrade");
+/// assert!(upgraded.starts_with("pbkdf2_sha256$200000$"));
+///
+/// let res = upgrade_if_needed("hello", encoded_password, 180000);
+/// assert!(matches!(res, Ok(None)));
+///
+/// let res = upgrade_if_needed("world", encoded_password, 200000);
+/// assert!(matches!(res, Err(Error::IncorrectPassword)));
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn upgrade_if_needed(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+    target_iterations: u32,
+) -> Result<Option<String>> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref();
+    if !django_auth(password, encoded_password)? {
+        return Err(Error::IncorrectPassword);
+    }
+
+    let target_iterations = match target_iterations {
+        0 => default_iterations(),
+        iterations => iterations,
+    };
+    if !needs_update(encoded_password, target_iterations)? {
+        return Ok(None);
+    }
+    django_encode_password(password, generate_salt(), target_iterations).map(Some)
+}
+
+/// Verify `password` against a legacy `old_encoded` password and, if it
+/// matches, re-encode it as pbkdf2_sha256 with `salt` and `iterations` (0
+/// means [default_iterations]), i.e. Django's upgrade on login.
+///
+/// Return `Some` new encoded password to store if the password matches, and
+/// `None` if it doesn't (or `old_encoded` is unusable). `salt` is validated
+/// like [django_encode_password] does, before verifying anything.
+///
+/// Any supported algorithm can be migrated, but the sha1, md5,
+/// unsalted_sha1, unsalted_md5 and crypt hashes are only verified with the
+/// `insecure-hashers` feature, without it they're rejected with
+/// [Error::FeatureNotEnabled].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// # #[cfg(feature = "insecure-hashers")] {
+/// let old_encoded = "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3";
+///
+/// let new_encoded = migrate_hash("l\u{e8}tmein", old_encoded, "btQDcwXF2RoK6Q", 0)
+///     .expect("migrate_hash error")
+///     .expect("incorrect password");
+/// assert!(new_encoded.starts_with("pbkdf2_sha256$180000$btQDcwXF2RoK6Q$"));
+///
+/// let res = migrate_hash("letmein", old_encoded, "btQDcwXF2RoK6Q", 0);
+/// assert!(matches!(res, Ok(None)));
+/// # }
+/// ```
+///
+pub fn migrate_hash(
+    password: impl AsRef<str>,
+    old_encoded: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<Option<String>> {
+    let password = password.as_ref();
+    let old_encoded = old_encoded.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    check_encode_salt(salt)?;
+    if !django_auth(password, old_encoded)? {
+        return Ok(None);
+    }
+    django_encode_password(password, salt, iterations).map(Some)
+}
+
+/// Raise the work factor of a pbkdf2 `encoded_password` without the plaintext
+/// password, e.g. for dormant accounts, by wrapping its hash in `iterations`
+/// more pbkdf2_sha256 rounds (0 means [default_iterations]) salted with
+/// `salt`, which is validated like [django_encode_password] does.
+///
+/// The result is a custom onion hash, which records the inner encoded
+/// password's parameters and each layer's:
+///
+/// ```text
+/// pbkdf2_wrapped$<algorithm>$<iterations>$<salt>$<iterations 1>$<salt 1>[$<iterations 2>$<salt 2>...]$<hash>
+/// ```
+///
+/// where `<algorithm>$<iterations>$<salt>` are the inner pbkdf2_sha256,
+/// pbkdf2_sha1 or pbkdf2_sha512 encoded password's, and `<hash>` is the
+/// base64 encoded 32 bytes
+/// `pbkdf2_sha256(... pbkdf2_sha256(inner hash, salt 1, iterations 1) ..., salt N, iterations N)`,
+/// the inner hash being the raw digest. Wrapping a `pbkdf2_wrapped` encoded
+/// password adds a layer.
+///
+/// [django_auth] peels the layers when verifying: it hashes the password with
+/// the inner parameters, then with each layer's in order. **Django can't
+/// verify these encoded passwords**, and [verify] asks for them to be
+/// rehashed, so that they are replaced by a regular one on the next login.
+/// The iterations of all the layers count towards [max_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+/// let wrapped = wrap_hash(encoded_password, "pepper", 1000).expect("wrap_hash error");
+///
+/// assert!(wrapped.starts_with("pbkdf2_wrapped$pbkdf2_sha256$180000$btQDcwXF2RoK6Q$1000$pepper$"));
+/// assert!(django_auth("hello", &wrapped).expect("django_auth error"));
+/// ```
+///
+pub fn wrap_hash(
+    encoded_password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    let salt = salt.as_ref();
+    check_encode_salt(salt)?;
+    if !is_password_usable(encoded_password) {
+        return Err(Error::InvalidEncodedPassword(
+            "unusable passwords can't be wrapped".into(),
+        ));
+    }
+    hashers::wrapped::wrap(encoded_password, salt, iterations)
+}
+
+/// The builtin default number of pbkdf2 iterations.
+pub const DEFAULT_ITERATIONS: u32 = 180000;
+
+/// The environment variable overriding [DEFAULT_ITERATIONS], see
+/// [default_iterations].
+pub const ITERATIONS_ENV_VAR: &str = "DJANGO_AUTH_ITERATIONS";
+
+// 0 until set by set_default_iterations
+static CURRENT_DEFAULT_ITERATIONS: AtomicU32 = AtomicU32::new(0);
+
+/// Return the number of iterations used when encoding with 0 iterations.
+///
+/// In order of precedence, this is:
+///
+/// 1. the value passed to [set_default_iterations],
+/// 2. the value of the `DJANGO_AUTH_ITERATIONS` environment variable (see
+///    [ITERATIONS_ENV_VAR]), read once on first use, if set to a positive
+///    integer (requires `std`),
+/// 3. [DEFAULT_ITERATIONS].
+///
+/// An explicit (non-zero) `iterations` argument of the encoding functions
+/// always takes precedence over all of them.
+pub fn default_iterations() -> u32 {
+    match CURRENT_DEFAULT_ITERATIONS.load(Ordering::Relaxed) {
+        0 => env_default_iterations(),
+        iterations => iterations,
+    }
+}
+
+#[cfg(feature = "std")]
+fn env_default_iterations() -> u32 {
+    static ITERATIONS: std::sync::OnceLock<u32> = std::sync::OnceLock::new();
+    *ITERATIONS.get_or_init(|| parse_env_iterations(std::env::var(ITERATIONS_ENV_VAR).ok()))
+}
+
+#[cfg(not(feature = "std"))]
+fn env_default_iterations() -> u32 {
+    DEFAULT_ITERATIONS
+}
+
+/// Parse the value of [ITERATIONS_ENV_VAR], falling back to
+/// [DEFAULT_ITERATIONS] when unset or invalid.
+#[cfg(feature = "std")]
+fn parse_env_iterations(value: Option<String>) -> u32 {
+    value
+        .and_then(|value| value.trim().parse().ok())
+        .filter(|&iterations| iterations > 0)
+        .unwrap_or(DEFAULT_ITERATIONS)
+}
+
+/// Set the number of iterations used when encoding with 0 iterations (and by
+/// [make_password]), e.g. to match the default of the Django version you
+/// target (260000 for Django 3.2, 600000 for Django 4.2...). It overrides the
+/// `DJANGO_AUTH_ITERATIONS` environment variable, passing 0 restores the
+/// default (see [default_iterations]).
+///
+/// The value is stored in an atomic, so this is safe to call from any thread
+/// at any time, it affects all the following encodings of every thread.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_default_iterations(1000);
+/// let encoded_password = django_encode_password("hello", "seasalt", 0)
+///     .expect("django_encode_password error");
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$1000$"));
+/// # set_default_iterations(0);
+/// ```
+///
+pub fn set_default_iterations(iterations: u32) {
+    CURRENT_DEFAULT_ITERATIONS.store(iterations, Ordering::Relaxed);
+}
+
+/// The builtin maximum length of passwords in bytes, Django's
+/// `MAXIMUM_PASSWORD_LENGTH`.
+pub const MAX_PASSWORD_LENGTH: usize = 4096;
+
+static CURRENT_MAX_PASSWORD_LENGTH: AtomicUsize = AtomicUsize::new(MAX_PASSWORD_LENGTH);
+
+/// Return the maximum length of passwords in bytes, [MAX_PASSWORD_LENGTH]
+/// unless changed by [set_max_password_length].
+pub fn max_password_length() -> usize {
+    CURRENT_MAX_PASSWORD_LENGTH.load(Ordering::Relaxed)
+}
+
+/// Set the maximum length of passwords in bytes. Passing 0 restores
+/// [MAX_PASSWORD_LENGTH], pass `usize::MAX` to disable the limit.
+///
+/// Longer passwords are rejected with [Error::InvalidPassword] by the encoding
+/// functions and by [django_auth], hashing huge inputs could otherwise be used
+/// to exhaust the CPU of a server (see CVE-2013-1443).
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_max_password_length(8);
+/// let res = django_encode_password("too long password", "seasalt", 1000);
+/// assert!(matches!(res, Err(Error::InvalidPassword(_))));
+/// # set_max_password_length(0);
+/// ```
+///
+pub fn set_max_password_length(len: usize) {
+    let len = if len == 0 { MAX_PASSWORD_LENGTH } else { len };
+    CURRENT_MAX_PASSWORD_LENGTH.store(len, Ordering::Relaxed);
+}
+
+/// The builtin maximum number of iterations accepted when verifying.
+pub const MAX_ITERATIONS: u32 = 10_000_000;
+
+static CURRENT_MAX_ITERATIONS: AtomicU32 = AtomicU32::new(MAX_ITERATIONS);
+
+/// Return the maximum number of iterations accepted when verifying,
+/// [MAX_ITERATIONS] unless changed by [set_max_iterations].
+pub fn max_iterations() -> u32 {
+    CURRENT_MAX_ITERATIONS.load(Ordering::Relaxed)
+}
+
+/// Set the maximum number of iterations of the pbkdf2 encoded passwords
+/// accepted when verifying. Passing 0 restores [MAX_ITERATIONS], pass
+/// `u32::MAX` to disable the limit.
+///
+/// An encoded password with more iterations is rejected with
+/// [Error::InvalidEncodedPassword] before hashing, otherwise whoever controls
+/// the stored hashes (e.g. of an import) could make each verification take
+/// minutes. Encoding is limited by [set_max_encode_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_max_iterations(100000);
+/// let res = django_auth(
+///     "hello",
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// );
+/// assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+/// # set_max_iterations(0);
+/// ```
+///
+pub fn set_max_iterations(iterations: u32) {
+    let iterations = if iterations == 0 {
+        MAX_ITERATIONS
+    } else {
+        iterations
+    };
+    CURRENT_MAX_ITERATIONS.store(iterations, Ordering::Relaxed);
+}
+
+/// The builtin maximum number of iterations accepted when encoding.
+pub const MAX_ENCODE_ITERATIONS: u32 = 10_000_000;
+
+static CURRENT_MAX_ENCODE_ITERATIONS: AtomicU32 = AtomicU32::new(MAX_ENCODE_ITERATIONS);
+
+/// Return the maximum number of iterations accepted when encoding,
+/// [MAX_ENCODE_ITERATIONS] unless changed by [set_max_encode_iterations].
+pub fn max_encode_iterations() -> u32 {
+    CURRENT_MAX_ENCODE_ITERATIONS.load(Ordering::Relaxed)
+}
+
+/// Set the maximum number of pbkdf2 iterations accepted when encoding (or
+/// deriving a key). Passing 0 restores [MAX_ENCODE_ITERATIONS], pass
+/// `u32::MAX` to disable the limit.
+///
+/// More iterations are rejected with [Error::InvalidIterations] before
+/// hashing, a mistyped count (or one taken from an untrusted configuration)
+/// could otherwise block the caller for hours. This is the encoding side of
+/// [set_max_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_max_encode_iterations(100000);
+/// let res = django_encode_password("hello", "seasalt", 180000);
+/// assert!(matches!(res, Err(Error::InvalidIterations(_))));
+/// # set_max_encode_iterations(0);
+/// ```
+///
+pub fn set_max_encode_iterations(iterations: u32) {
+    let iterations = if iterations == 0 {
+        MAX_ENCODE_ITERATIONS
+    } else {
+        iterations
+    };
+    CURRENT_MAX_ENCODE_ITERATIONS.store(iterations, Ordering::Relaxed);
+}
+
+/// The builtin maximum cost of the argon2 and scrypt encoded passwords
+/// accepted when verifying, in KiB, see [set_max_memory_cost]: 2 GiB, RFC
+/// 9106's recommended argon2 parameters (m=2 GiB, t=1), about 10 times
+/// Django's default argon2 cost.
+pub const MAX_MEMORY_COST: u32 = 2 * 1024 * 1024;
+
+static CURRENT_MAX_MEMORY_COST: AtomicU32 = AtomicU32::new(MAX_MEMORY_COST);
+
+/// Return the maximum cost of the argon2 and scrypt encoded passwords
+/// accepted when verifying, in KiB, [MAX_MEMORY_COST] unless changed by
+/// [set_max_memory_cost].
+pub fn max_memory_cost() -> u32 {
+    CURRENT_MAX_MEMORY_COST.load(Ordering::Relaxed)
+}
+
+/// Set the maximum cost of the argon2 and scrypt encoded passwords accepted
+/// when verifying, in KiB of memory filled: the memory cost times the time
+/// cost for argon2 (`m` × `t`), 128 × N × r × p bytes for scrypt. Passing 0
+/// restores [MAX_MEMORY_COST], pass `u32::MAX` to disable the limit.
+///
+/// A costlier encoded password is rejected with [Error::InvalidEncodedPassword]
+/// before hashing, otherwise whoever controls the stored hashes could make
+/// each verification allocate terabytes or take hours. This is the argon2 and
+/// scrypt counterpart of [set_max_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_max_memory_cost(1024);
+/// # #[cfg(feature = "argon2")] {
+/// let res = django_auth(
+///     "secret",
+///     "argon2$argon2id$v=19$m=102400,t=2,p=8$Y041dExhNkljRUUy$TMa6A8fPJhCAUXRhJXCXdw",
+/// );
+/// assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+/// # }
+/// # set_max_memory_cost(0);
+/// ```
+///
+pub fn set_max_memory_cost(kib: u32) {
+    let kib = if kib == 0 { MAX_MEMORY_COST } else { kib };
+    CURRENT_MAX_MEMORY_COST.store(kib, Ordering::Relaxed);
+}
+
+/// The builtin maximum cost of the bcrypt encoded passwords accepted when
+/// verifying, see [set_max_bcrypt_cost]: 16, i.e. 16 times the rounds of
+/// Django's default cost of 12.
+pub const MAX_BCRYPT_COST: u32 = 16;
+
+static CURRENT_MAX_BCRYPT_COST: AtomicU32 = AtomicU32::new(MAX_BCRYPT_COST);
+
+/// Return the maximum cost of the bcrypt encoded passwords accepted when
+/// verifying, [MAX_BCRYPT_COST] unless changed by [set_max_bcrypt_cost].
+pub fn max_bcrypt_cost() -> u32 {
+    CURRENT_MAX_BCRYPT_COST.load(Ordering::Relaxed)
+}
+
+/// Set the maximum cost of the bcrypt encoded passwords accepted when
+/// verifying. Passing 0 restores [MAX_BCRYPT_COST], bcrypt itself allows
+/// costs up to 31.
+///
+/// Encoded passwords with a higher cost return
+/// [Error::InvalidEncodedPassword] without being hashed: the number of
+/// rounds doubles with each increment, a tampered `$2b$31$` hash would take
+/// days to verify. This is the bcrypt counterpart of [set_max_iterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_max_bcrypt_cost(10);
+/// # #[cfg(feature = "bcrypt")] {
+/// let res = django_auth(
+///     "secret",
+///     "bcrypt$$2b$12$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+/// );
+/// assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+/// # }
+/// # set_max_bcrypt_cost(0);
+/// ```
+///
+pub fn set_max_bcrypt_cost(cost: u32) {
+    let cost = if cost == 0 { MAX_BCRYPT_COST } else { cost };
+    CURRENT_MAX_BCRYPT_COST.store(cost, Ordering::Relaxed);
+}
+
+static DUMMY_HASH_ON_ERROR: AtomicBool = AtomicBool::new(false);
+
+/// Return whether [django_auth] hashes the password when the encoded password
+/// can't be verified, see [set_dummy_hash_on_error].
+pub fn dummy_hash_on_error() -> bool {
+    DUMMY_HASH_ON_ERROR.load(Ordering::Relaxed)
+}
+
+/// Hardening: when enabled, [django_auth] (and [django_auth_bytes],
+/// [check_password]...) hashes the password with pbkdf2_sha256 and
+/// [default_iterations] before returning an error for a malformed encoded
+/// password, or `Ok(false)` for an unusable one. Disabled by default.
+///
+/// Otherwise these paths return right away while a wrong password against a
+/// valid hash takes the full hashing time, so response times tell an attacker
+/// which accounts have a usable hash, like Django runs the hasher once for
+/// unknown users to mitigate user enumeration.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// set_dummy_hash_on_error(true);
+/// // takes about as long as verifying a default pbkdf2_sha256 hash
+/// assert!(django_auth("hello", "garbage").is_err());
+/// # set_dummy_hash_on_error(false);
+/// ```
+///
+pub fn set_dummy_hash_on_error(enabled: bool) {
+    DUMMY_HASH_ON_ERROR.store(enabled, Ordering::Relaxed);
+}
+
+/// Spend the time of a default pbkdf2_sha256 verification.
+fn run_dummy_hash(password: &[u8]) {
+    let _ = Pbkdf2Sha256Hasher::default().encode_bytes(password, "dummysalt");
+}
+
+/// Reject passwords longer than [max_password_length].
+pub(crate) fn check_password_length(password: &[u8]) -> Result<()> {
+    let max = max_password_length();
+    if password.len() > max {
+        return Err(password_too_long(max));
+    }
+    Ok(())
+}
+
+pub(crate) fn password_too_long(max: usize) -> Error {
+    Error::InvalidPassword(format!("password should not be longer than {max} bytes"))
+}
+
+/// Encode `password` in [Django way][1].
+///
+/// When `iterations` is 0, [default_iterations] is used (which can be set with
+/// [set_default_iterations] or the `DJANGO_AUTH_ITERATIONS` environment
+/// variable). More than [max_encode_iterations] iterations (10 million unless
+/// changed by [set_max_encode_iterations]) are rejected with
+/// [Error::InvalidIterations], since they would block the caller.
+///
+/// An empty `password` is rejected with [Error::InvalidPassword], since it's
+/// most likely a bug (Django uses an unusable password for accounts without a
+/// password), and an empty `salt` with [Error::InvalidSalt], since it weakens
+/// the hash. Use [django_encode_password_allow_empty] to encode them anyway.
+/// Passwords longer than [max_password_length] are rejected too.
+///
+/// The salt should only contain `[a-zA-Z0-9]` characters, like the salts
+/// generated by Django (and [generate_salt]), otherwise [Error::InvalidSalt]
+/// is returned. To use other characters (except `$`, the separator), encode
+/// with [Pbkdf2Sha256Hasher] directly, which doesn't restrict the salt.
+///
+/// See [Pbkdf2Sha256] for a builder, which generates a random salt by default.
+///
+/// Intermediate buffers derived from `password` are zeroized once they're no
+/// longer needed. To control the lifetime of the password itself, keep it in a
+/// `zeroize::Zeroizing<String>`, which derefs to `&str`.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let password = "hello";
+/// let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 0)
+///     .expect("django_encode_password error");
+///
+/// assert_eq!(
+///     encoded_password,
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+/// );
+/// let res = django_auth(password, &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+/// [1]: https://docs.djangoproject.com/en/5.0/topics/auth/passwords/
+///
+pub fn django_encode_password(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    django_encode_password_bytes(password.as_ref().as_bytes(), salt, iterations)
+}
+
+/// Return the `(algorithm, iterations, salt)` which [django_encode_password]
+/// would encode with, after validating `salt` and resolving 0 `iterations` to
+/// [default_iterations], without hashing anything, e.g. to preview the
+/// settings in a UI.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let (algorithm, iterations, salt) = encode_params("seasalt", 0).expect("encode_params error");
+/// assert_eq!(algorithm, "pbkdf2_sha256");
+/// assert_eq!(iterations, default_iterations());
+/// assert_eq!(salt, "seasalt");
+///
+/// assert!(encode_params("sea$salt", 1000).is_err());
+/// ```
+///
+pub fn encode_params(salt: impl AsRef<str>, iterations: u32) -> Result<(String, u32, String)> {
+    let salt = salt.as_ref();
+    check_encode_salt(salt)?;
+    let iterations = if iterations == 0 {
+        default_iterations()
+    } else {
+        iterations
+    };
+    hashers::pbkdf2::check_encode_iterations("pbkdf2_sha256", iterations)?;
+    Ok(("pbkdf2_sha256".to_owned(), iterations, salt.to_owned()))
+}
+
+/// Same as [django_encode_password] for a password which may not be valid
+/// UTF-8.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = django_encode_password_bytes(b"\xffhello", "seasalt", 1000)
+///     .expect("django_encode_password_bytes error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$1000$seasalt$"));
+/// let res = django_auth_bytes(b"\xffhello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_bytes(
+    password: impl AsRef<[u8]>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    Pbkdf2Sha256::new()
+        .iterations(iterations)
+        .salt(salt)
+        .encode_bytes(password)
+}
+
+/// The maximum length of the keys returned by [derive_key]: 255 blocks of
+/// 32 bytes, like HKDF-SHA256, far more than any key needs.
+pub const MAX_DERIVED_KEY_LEN: usize = 255 * 32;
+
+/// Derive a `len` bytes key from `password` and `salt` with pbkdf2_sha256,
+/// i.e. the raw hash underneath [django_encode_password] (which derives 32
+/// bytes and base64 encodes them), e.g. for custom storage schemes.
+///
+/// Like the encoding functions, 0 `iterations` means [default_iterations].
+/// Unlike them, any `salt` is accepted, and the password may be empty. `len`
+/// should be 1 to [MAX_DERIVED_KEY_LEN] bytes, otherwise
+/// [Error::InvalidLength] is returned.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let key = derive_key("hello", "seasalt", 1000, 16).expect("derive_key error");
+/// assert_eq!(key.len(), 16);
+/// ```
+///
+pub fn derive_key(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+    len: usize,
+) -> Result<Vec<u8>> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_password_length(password.as_bytes())?;
+    if !(1..=MAX_DERIVED_KEY_LEN).contains(&len) {
+        return Err(Error::InvalidLength(format!(
+            "key should be 1 to {MAX_DERIVED_KEY_LEN} bytes, got {len}"
+        )));
+    }
+    let iterations = if iterations == 0 {
+        default_iterations()
+    } else {
+        iterations
+    };
+    hashers::pbkdf2::check_encode_iterations("pbkdf2_sha256", iterations)?;
+    let mut key = hashers::pbkdf2::hash_password(
+        "pbkdf2_sha256",
+        password.as_bytes(),
+        salt,
+        iterations,
+        len,
+    )?;
+    Ok(core::mem::take(&mut *key))
+}
+
+/// Same as [django_encode_password], but accepts an empty `password` or
+/// `salt`, like Django's `make_password` does.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// assert!(django_encode_password("", "seasalt", 1000).is_err());
+///
+/// let encoded_password = django_encode_password_allow_empty("", "seasalt", 1000)
+///     .expect("django_encode_password_allow_empty error");
+/// let res = django_auth("", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_allow_empty(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_password_length(password.as_bytes())?;
+    check_salt_charset(salt)?;
+    Pbkdf2Sha256Hasher { iterations }.encode(password, salt)
+}
+
+/// Reject empty or too long passwords, see [django_encode_password].
+pub(crate) fn check_encode_password(password: &[u8]) -> Result<()> {
+    check_password_length(password)?;
+    if password.is_empty() {
+        return Err(Error::InvalidPassword(
+            "password should not be empty".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Reject empty salts, or salts with other characters than `[a-zA-Z0-9]`.
+pub(crate) fn check_encode_salt(salt: &str) -> Result<()> {
+    if salt.is_empty() {
+        return Err(Error::InvalidSalt("salt should not be empty".into()));
+    }
+    check_salt_charset(salt)
+}
+
+/// Reject salts with other characters than `[a-zA-Z0-9]`, the charset of
+/// Django's salts, see [django_encode_password].
+fn check_salt_charset(salt: &str) -> Result<()> {
+    if !salt.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return Err(Error::InvalidSalt(
+            "salt should only contain [a-zA-Z0-9] characters".into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Encode `password` with `algorithm` and its default parameters (see
+/// [Algorithm::hasher]), e.g. an algorithm read from a config file.
+///
+/// Empty passwords and passwords longer than [max_password_length] are
+/// rejected like [django_encode_password] does, and `salt` has to suit
+/// `algorithm` (bcrypt takes a 22 characters bcrypt salt, argon2 at least 8
+/// characters...).
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let algorithm: Algorithm = "pbkdf2_sha256".parse().expect("unknown algorithm");
+/// let encoded_password =
+///     django_encode_password_with_algorithm(algorithm, "hello", "btQDcwXF2RoK6Q")
+///         .expect("django_encode_password_with_algorithm error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$180000$btQDcwXF2RoK6Q$"));
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_with_algorithm(
+    algorithm: Algorithm,
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    algorithm.hasher()?.encode(password, salt)
+}
+
+/// Same as [django_encode_password], but accepts any non-empty salt, e.g. one
+/// from an external salt generator: `$` (the separator) and `%` are stored
+/// percent-encoded as `%24` and `%25`, while the hash is computed with the
+/// salt as is.
+///
+/// Verify the result with [django_auth_escaped], [django_auth] doesn't decode
+/// the salt. Salts without `$` or `%` are stored unchanged, so the result is
+/// the same as [django_encode_password]'s for Django's salts.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = django_encode_password_escaped("hello", "sea$salt", 1000)
+///     .expect("django_encode_password_escaped error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$1000$sea%24salt$"));
+/// let res = django_auth_escaped("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_escaped(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    if salt.is_empty() {
+        return Err(Error::InvalidSalt("salt should not be empty".into()));
+    }
+    hashers::pbkdf2::encode_escaped("pbkdf2_sha256", password.as_bytes(), salt, iterations)
+}
+
+/// Same as [django_auth], but percent-decodes the salt of pbkdf2 encoded
+/// passwords, see [django_encode_password_escaped].
+///
+/// Django's salts never contain a `%`, so regular pbkdf2 encoded passwords
+/// verify the same as with [django_auth].
+pub fn django_auth_escaped(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref().trim_ascii();
+    check_password_length(password.as_bytes())?;
+    if !is_password_usable(encoded_password) {
+        return Ok(false);
+    }
+    match identify_hasher(encoded_password)? {
+        "pbkdf2_sha256" | "pbkdf2_sha1" | "pbkdf2_sha512" => {
+            hashers::pbkdf2::verify_escaped(password.as_bytes(), encoded_password)
+        }
+        _ => django_auth(password, encoded_password),
+    }
+}
+
+/// Same as [django_auth], but percent-decodes `encoded_password` first, e.g.
+/// when it was passed through a URL query string, with `$` as `%24` and `+`
+/// as `%2B`.
+///
+/// `+` is kept as is rather than decoded to a space, since it's part of the
+/// base64 alphabet. [django_auth] never decodes, as a `%` may be part of a
+/// valid encoded password.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let res = django_auth_urlencoded(
+///     "hello",
+///     "pbkdf2_sha256%24180000%24btQDcwXF2RoK6Q%24D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU%3D",
+/// )
+/// .expect("django_auth_urlencoded error");
+/// assert!(res);
+/// ```
+///
+pub fn django_auth_urlencoded(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+) -> Result<bool> {
+    let encoded_password = percent_decode(encoded_password.as_ref())?;
+    django_auth(password, encoded_password)
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let invalid = || {
+        Error::InvalidEncodedPassword(
+            "encoded password should only contain valid percent escapes".into(),
+        )
+    };
+
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut bytes = s.bytes();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            decoded.push(b);
+            continue;
+        }
+        let mut digit = || {
+            bytes
+                .next()
+                .and_then(|d| char::from(d).to_digit(16))
+                .ok_or_else(invalid)
+        };
+        let high = digit()?;
+        let low = digit()?;
+        // two hex digits always fit in a u8
+        decoded.push((high * 16 + low) as u8);
+    }
+    String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+/// Same as [django_encode_password], but store the hash as lowercase hex
+/// instead of base64, under the `pbkdf2_sha256_hex` algorithm, for consumers
+/// which expect hex digests (see [Pbkdf2Sha256HexHasher]).
+///
+/// [django_auth] verifies these encoded passwords, but **Django can't**: the
+/// distinct algorithm name only prevents them from being mistaken for Django's
+/// pbkdf2_sha256 ones.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = django_encode_password_hex("hello", "seasalt", 1000)
+///     .expect("django_encode_password_hex error");
+///
+/// assert_eq!(
+///     encoded_password,
+///     "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0afb1e91dce39cc197e36c84983d26942"
+/// );
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_hex(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    check_encode_salt(salt)?;
+    Pbkdf2Sha256HexHasher { iterations }.encode(password, salt)
+}
+
+/// Same as [django_encode_password], but with a binary `salt`, e.g. from a key
+/// derivation scheme, which is stored base64 encoded under the
+/// `pbkdf2_sha256_b64salt` algorithm (see [Pbkdf2Sha256B64SaltHasher]).
+///
+/// Any non-empty salt is accepted, so it can have more entropy than Django's
+/// alphanumeric salts. [django_auth] verifies these encoded passwords, but
+/// **Django can't**: the distinct algorithm name only prevents them from being
+/// mistaken for Django's pbkdf2_sha256 ones.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password =
+///     django_encode_password_with_salt_bytes("hello", b"\x00\xffsea$salt", 1000)
+///         .expect("django_encode_password_with_salt_bytes error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256_b64salt$1000$AP9zZWEkc2FsdA==$"));
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+pub fn django_encode_password_with_salt_bytes(
+    password: impl AsRef<str>,
+    salt: impl AsRef<[u8]>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    if salt.is_empty() {
+        return Err(Error::InvalidSalt("salt should not be empty".into()));
+    }
+    Pbkdf2Sha256B64SaltHasher { iterations }.encode_salt_bytes(password.as_bytes(), salt)
+}
+
+/// Encode `password` the way Django's `PBKDF2SHA1PasswordHasher` does.
+///
+/// Arguments are the same as [django_encode_password].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let password = "hello";
+/// let encoded_password = django_encode_password_pbkdf2_sha1(password, "btQDcwXF2RoK6Q", 0)
+///     .expect("django_encode_password_pbkdf2_sha1 error");
+///
+/// assert_eq!(
+///     encoded_password,
+///     "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8="
+/// );
+/// let res = django_auth(password, &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+#[cfg(feature = "legacy-sha1")]
+pub fn django_encode_password_pbkdf2_sha1(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    check_encode_salt(salt)?;
+    Pbkdf2Sha1Hasher { iterations }.encode(password, salt)
+}
+
+/// Encode `password` with the default pbkdf2_sha256 algorithm and iterations,
+/// using a random salt (see [generate_salt]), like Django's `make_password`.
+///
+/// Unlike Django, an empty password is rejected, see [django_encode_password].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = make_password("hello").expect("make_password error");
+///
+/// assert!(encoded_password.starts_with("pbkdf2_sha256$180000$"));
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn make_password(password: impl AsRef<str>) -> Result<String> {
+    Pbkdf2Sha256::default().encode(password.as_ref())
+}
+
+/// Same as [make_password], drawing the salt from `rng` (see
+/// [generate_salt_with_rng]), e.g. a seeded RNG to get a deterministic output
+/// in tests.
+///
+/// Unlike [make_password], this is available without `std`.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// use rand::rngs::mock::StepRng;
+///
+/// let a = make_password_with_rng("hello", &mut StepRng::new(0, 1 << 26))
+///     .expect("make_password_with_rng error");
+/// let b = make_password_with_rng("hello", &mut StepRng::new(0, 1 << 26))
+///     .expect("make_password_with_rng error");
+///
+/// assert_eq!(a, b);
+/// assert!(django_auth("hello", &a).expect("auth failed"));
+/// ```
+///
+pub fn make_password_with_rng(password: impl AsRef<str>, rng: &mut impl RngCore) -> Result<String> {
+    let password = password.as_ref();
+    Pbkdf2Sha256::default()
+        .salt(generate_salt_with_rng(rng, 12))
+        .encode(password)
+}
+
+/// Generate a random 12-characters salt, see [generate_salt_with_len].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let salt = generate_salt();
+///
+/// assert_eq!(salt.len(), 12);
+/// let encoded_password = django_encode_password("hello", &salt, 0)
+///     .expect("django_encode_password error");
+/// ```
+///
+#[cfg(feature = "std")]
+pub fn generate_salt() -> String {
+    generate_salt_with_len(12)
+}
+
+/// Generate a random salt of `len` characters drawn from `[a-zA-Z0-9]` (the
+/// charset of Django's `get_random_string`), using a cryptographically secure
+/// RNG. The salt never contains a `$`, so it's always valid for
+/// [django_encode_password].
+#[cfg(feature = "std")]
+pub fn generate_salt_with_len(len: usize) -> String {
+    generate_salt_with_rng(&mut rand::thread_rng(), len)
+}
+
+/// Same as [generate_salt_with_len], drawing the characters from `rng`.
+///
+/// This is meant for deterministic tests with a seeded (or mock) RNG, or for
+/// platforms without `std` which provide their own entropy source. Outside of
+/// tests, `rng` must be a cryptographically secure RNG, which is what
+/// [generate_salt] uses.
+pub fn generate_salt_with_rng(rng: &mut impl RngCore, len: usize) -> String {
+    (0..len)
+        .map(|_| char::from(rng.sample(Alphanumeric)))
+        .collect()
+}
+
+/// Compare `a` and `b` in constant time (for inputs of the same length).
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Encode `password` the way Django's `BCryptPasswordHasher` does, with a
+/// random salt and the given `cost` factor (0 means Django's default: 12).
+///
+/// Note that bcrypt silently truncates passwords to 72 bytes, so two
+/// passwords sharing the same first 72 bytes produce matching hashes. Use
+/// (Django's) bcrypt_sha256 if that is a concern.
+///
+/// Empty passwords and passwords longer than [max_password_length] are
+/// rejected like [django_encode_password] does, and costs outside of 4..=31
+/// with [Error::InvalidIterations].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = django_encode_password_bcrypt("hello", 4)
+///     .expect("django_encode_password_bcrypt error");
+///
+/// assert!(encoded_password.starts_with("bcrypt$$2b$04$"));
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+#[cfg(feature = "bcrypt")]
+pub fn django_encode_password_bcrypt(password: impl AsRef<str>, cost: u32) -> Result<String> {
+    let password = password.as_ref();
+    check_encode_password(password.as_bytes())?;
+    let cost = if cost == 0 {
+        BcryptHasher::default().cost
+    } else {
+        cost
+    };
+    BcryptHasher { cost }.encode(password, &hashers::bcrypt::generate_salt())
+}
+
+/// Encode `password` the way Django's `Argon2PasswordHasher` does, with
+/// argon2id version 19 and custom parameters: `memory_kib` KiB of memory,
+/// `time_cost` iterations and `parallelism` lanes (Django's defaults are
+/// 102400, 2 and 8, see [Argon2Hasher::default]).
+///
+/// The result is `argon2$argon2id$v=19$m=<memory_kib>,t=<time_cost>,p=<parallelism>$<salt>$<hash>`,
+/// the salt and the 32 bytes hash being base64 encoded without padding, like
+/// Django (through argon2-cffi) stores them. argon2 requires salts of at least
+/// 8 bytes, and at least 8 KiB of memory per lane: shorter salts return
+/// [Error::InvalidSalt] and parameters argon2 rejects [Error::InvalidIterations].
+///
+/// Empty passwords and passwords longer than [max_password_length] are
+/// rejected like [django_encode_password] does.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+/// let encoded_password = argon2_encode("hello", "btQDcwXF2RoK6Q", 64, 1, 1)
+///     .expect("argon2_encode error");
+///
+/// assert!(encoded_password.starts_with("argon2$argon2id$v=19$m=64,t=1,p=1$YnRRRGN3WEYyUm9LNlE$"));
+/// let res = django_auth("hello", &encoded_password).expect("auth failed");
+/// assert!(res);
+/// ```
+///
+#[cfg(feature = "argon2")]
+pub fn argon2_encode(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    memory_kib: u32,
+    time_cost: u32,
+    parallelism: u32,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    check_encode_password(password.as_bytes())?;
+    Argon2Hasher {
+        memory_cost: memory_kib,
+        time_cost,
+        parallelism,
+    }
+    .encode(password, salt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_eq() {
+        let res = django_auth("hello", "pbkdf2_sha256$1000$seasalt");
+        assert_eq!(
+            res,
+            Err(Error::InvalidEncodedPassword(
+                "encoded password should have 4 components separated by '$'".to_owned()
+            ))
+        );
+        assert_eq!(
+            django_auth("hello", "foo$salt$hash"),
+            Err(Error::UnsupportedAlgorithm("foo".to_owned()))
+        );
+        assert_ne!(
+            Error::InvalidSalt("foo".to_owned()),
+            Error::InvalidPassword("foo".to_owned())
+        );
+        assert_eq!(Error::IncorrectPassword, Error::IncorrectPassword);
+    }
+
+    #[cfg(not(feature = "argon2"))]
+    #[test]
+    fn test_feature_not_enabled() {
+        let encoded_password =
+            "argon2$argon2id$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q";
+        match django_auth("hello", encoded_password) {
+            Err(e @ Error::FeatureNotEnabled(_)) => assert_eq!(
+                e.to_string(),
+                "feature not enabled: enable the `argon2` feature to support argon2"
+            ),
+            res => panic!("{res:?}"),
+        }
+        assert!(matches!(
+            Algorithm::Argon2.hasher(),
+            Err(Error::FeatureNotEnabled(_))
+        ));
+
+        // unknown algorithms are still unsupported
+        let res = django_auth("hello", "argon3$argon2id$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$hash");
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_django_auth() {
+        let res = django_auth(
+            "hello",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        )
+        .unwrap();
+
+        assert!(res);
+
+        let res = django_auth(
+            "world",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        )
+        .unwrap();
+        assert!(!res);
+
+        // a truncated hash is a corrupted encoded password, not a wrong password
+        let res = django_auth(
+            "hello",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        let res = django_auth("world", "abc$edf");
+        assert!(res.is_err());
+
+        // unusable passwords never match
+        assert!(!django_auth("hello", "!").unwrap());
+        assert!(!django_auth("hello", "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5").unwrap());
+        assert!(!django_auth(
+            "hello",
+            "!pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        )
+        .unwrap());
+
+        // trailing components are part of the hash
+        let res = django_auth(
+            "hello",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=$",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        let res = django_auth("hello", "pbkdf2_sha256$notanumber$salt$hash");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+
+        // too many iterations are rejected before hashing (which would take minutes)
+        let res = django_auth(
+            "hello",
+            "pbkdf2_sha256$4294967295$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = django_auth(
+            "hello",
+            format_encoded("pbkdf2_sha256", MAX_ITERATIONS + 1, "salt", "aGVsbG8="),
+        );
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+    }
+
+    #[test]
+    fn test_djaongo_encode_password() {
+        let password = "hello";
+        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 0)
+            .expect("django_encode_password failed");
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+        let res = django_auth(password, &encoded_password).expect("auth failed");
+        assert!(res);
+
+        let password = "hello";
+        let res = django_encode_password(password, "btQDcwXF$2RoK6Q", 0);
+        assert!(res.is_err());
+
+        let password = "hello";
+        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Q", 10)
+            .expect("django_encode_password failed");
+        assert_ne!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+
+        let password = "hello";
+        let encoded_password = django_encode_password(password, "btQDcwXF2RoK6Qx", 0)
+            .expect("django_encode_password failed");
+        assert_ne!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_django_auth_async() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(django_auth_async("hello", &encoded_password).await.unwrap());
+        assert!(!django_auth_async("world", &encoded_password).await.unwrap());
+        assert!(django_auth_async("hello", "abc$edf").await.is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        let encoded_password =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert_eq!(
+            verify("hello", encoded_password).unwrap(),
+            Verification::Valid
+        );
+        assert_eq!(
+            verify("world", encoded_password).unwrap(),
+            Verification::Invalid
+        );
+
+        // fewer iterations than the default
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let res = verify("hello", &encoded_password).unwrap();
+        assert_eq!(res, Verification::ValidButNeedsRehash);
+        assert!(res.is_valid());
+        assert_eq!(
+            verify("world", &encoded_password).unwrap(),
+            Verification::Invalid
+        );
+
+        // not the preferred algorithm
+        #[cfg(feature = "legacy-sha1")]
+        {
+            let encoded_password = "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=";
+            let res = verify("hello", encoded_password).unwrap();
+            assert_eq!(res, Verification::ValidButNeedsRehash);
+        }
+
+        let res = verify("hello", "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5").unwrap();
+        assert_eq!(res, Verification::UnusablePassword);
+        assert!(!res.is_valid());
+
+        assert!(verify("hello", "abc$edf").is_err());
+    }
+
+    #[test]
+    fn test_django_auth_with_policy() {
+        let encoded_password =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert!(django_auth_with_policy("hello", encoded_password, &["pbkdf2_sha256"]).unwrap());
+        assert!(!django_auth_with_policy("world", encoded_password, &["pbkdf2_sha256"]).unwrap());
+
+        // rejected even if the password matches
+        for allowed in [&["pbkdf2_sha1", "argon2"][..], &[]] {
+            let res = django_auth_with_policy("hello", encoded_password, allowed);
+            assert!(matches!(res, Err(Error::AlgorithmNotAllowed(a)) if a == "pbkdf2_sha256"));
+        }
+
+        let res =
+            django_auth_with_policy("hello", "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5", &[]);
+        assert!(!res.unwrap());
+        assert!(django_auth_with_policy("hello", "abc", &["pbkdf2_sha256"]).is_err());
+    }
+
+    #[test]
+    fn test_django_auth_expect() {
+        let encoded_password =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert!(django_auth_expect("hello", encoded_password, Algorithm::Pbkdf2Sha256).unwrap());
+        assert!(!django_auth_expect("world", encoded_password, Algorithm::Pbkdf2Sha256).unwrap());
+
+        // a stored algorithm other than the expected one is rejected, even if
+        // the password matches
+        for expected in [Algorithm::Argon2, Algorithm::Pbkdf2Sha1, Algorithm::Md5] {
+            let res = django_auth_expect("hello", encoded_password, expected);
+            assert!(matches!(res, Err(Error::AlgorithmNotAllowed(a)) if a == "pbkdf2_sha256"));
+        }
+
+        let res = django_auth_expect("hello", "!", Algorithm::Pbkdf2Sha256);
+        assert!(!res.unwrap());
+    }
+
+    #[test]
+    fn test_django_auth_with_min_salt_len() {
+        let encoded_password =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        for min_salt_len in [0, 14] {
+            assert!(
+                django_auth_with_min_salt_len("hello", encoded_password, min_salt_len).unwrap()
+            );
+            assert!(
+                !django_auth_with_min_salt_len("world", encoded_password, min_salt_len).unwrap()
+            );
+        }
+        // rejected even if the password matches
+        let res = django_auth_with_min_salt_len("hello", encoded_password, 15);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+
+        let encoded_password = django_encode_password_allow_empty("hello", "", 1000).unwrap();
+        assert!(django_auth_with_min_salt_len("hello", &encoded_password, 0).unwrap());
+        let res = django_auth_with_min_salt_len("hello", &encoded_password, 1);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+
+        let res =
+            django_auth_with_min_salt_len("hello", "!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5", 64);
+        assert!(!res.unwrap());
+        assert!(django_auth_with_min_salt_len("hello", "abc$edf", 0).is_err());
+    }
+
+    #[test]
+    fn test_stored_salt() {
+        for (encoded_password, salt) in [
+            (
+                "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+                "btQDcwXF2RoK6Q",
+            ),
+            ("pbkdf2_sha256$1000$$hash", ""),
+            #[cfg(feature = "argon2")]
+            (
+                "argon2$argon2i$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+                "c2FsdHNhbHQ",
+            ),
+            #[cfg(feature = "argon2")]
+            (
+                "argon2$argon2i$m=8,t=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+                "c2FsdHNhbHQ",
+            ),
+            #[cfg(feature = "scrypt")]
+            (
+                "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+                "seasalt",
+            ),
+            #[cfg(feature = "bcrypt")]
+            (
+                "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+                "Ju3n0NxMoXrbC2LMcRKDXu",
+            ),
+            #[cfg(feature = "insecure-hashers")]
+            (
+                "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+                "seasalt",
+            ),
+            #[cfg(feature = "insecure-hashers")]
+            ("md5$$88a434c88cca4e900f7874cd98123f43", ""),
+        ] {
+            assert_eq!(stored_salt(encoded_password).unwrap(), salt);
+        }
+
+        for encoded_password in [
+            "pbkdf2_sha256$180000",
+            "pbkdf2_sha256$abc$salt$hash",
+            #[cfg(feature = "argon2")]
+            "argon2$argon2i",
+            #[cfg(feature = "bcrypt")]
+            "bcrypt$$2b$04$Ju3n0N",
+            #[cfg(feature = "scrypt")]
+            "scrypt$1024",
+        ] {
+            let res = stored_salt(encoded_password);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded_password}"
+            );
+        }
+        let res = stored_salt("foo$salt$hash");
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_get_salt() {
+        for (encoded_password, salt) in [
+            (
+                "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+                "btQDcwXF2RoK6Q",
+            ),
+            ("pbkdf2_sha256$1000$$hash", ""),
+            #[cfg(feature = "legacy-sha1")]
+            (
+                "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+                "btQDcwXF2RoK6Q",
+            ),
+            ("pbkdf2_sha512$1000$seasalt$9EmDloRvwcysQnIq3lo8Mzne3FaccLadeTaYarxzxpZNs2P3uy7gMkPw0k60qPWt7i5nMkjW6Ut6jR/sBTnHyQ==", "seasalt"),
+            (
+                "pbkdf2_sha256_b64salt$1000$c2Vhc2FsdA==$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+                "seasalt",
+            ),
+            (
+                "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$\
+                 LWChDx0rILwcEjewSZK7lj3iOKdzMNlcQ4JzPCPrWNo=",
+                "seasalt",
+            ),
+            #[cfg(feature = "argon2")]
+            (
+                "argon2$argon2i$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+                "saltsalt",
+            ),
+            #[cfg(feature = "scrypt")]
+            (
+                "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+                "seasalt",
+            ),
+            #[cfg(feature = "insecure-hashers")]
+            (
+                "sha1$seasalt$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+                "seasalt",
+            ),
+        ] {
+            assert_eq!(get_salt(encoded_password).unwrap(), salt);
+        }
+
+        for encoded_password in [
+            "pbkdf2_sha256$180000",
+            "garbage",
+            "!",
+            "pbkdf2_sha256_b64salt$1000$AP9zZWEkc2FsdA==$hash",
+            #[cfg(feature = "argon2")]
+            "argon2$argon2i$v=19$m=8,t=1,p=1$/w$YC9+jJCrQhs5R6db7LlN8Q",
+            #[cfg(feature = "bcrypt")]
+            "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+        ] {
+            let res = get_salt(encoded_password);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded_password}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_django_encode_password_empty() {
+        let res = django_encode_password("", "seasalt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_bytes(b"", "seasalt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password("hello", "", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+
+        let encoded_password = django_encode_password_allow_empty("", "seasalt", 1000).unwrap();
+        assert!(encoded_password.starts_with("pbkdf2_sha256$1000$seasalt$"));
+        assert!(django_auth("", &encoded_password).unwrap());
+        assert!(!django_auth("hello", &encoded_password).unwrap());
+
+        let encoded_password = django_encode_password_allow_empty("hello", "", 1000).unwrap();
+        assert!(encoded_password.starts_with("pbkdf2_sha256$1000$$"));
+        assert!(django_auth("hello", &encoded_password).unwrap());
+    }
+
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_django_auth_timed() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let (res, elapsed) = django_auth_timed("hello", &encoded_password).unwrap();
+        assert!(res);
+        assert!(elapsed > std::time::Duration::ZERO);
+        assert!(!django_auth_timed("world", &encoded_password).unwrap().0);
+
+        assert!(django_auth_timed("hello", "pbkdf2_sha256$1000$seasalt$").is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_env_iterations() {
+        assert_eq!(parse_env_iterations(None), DEFAULT_ITERATIONS);
+        assert_eq!(parse_env_iterations(Some("600000".to_owned())), 600000);
+        assert_eq!(parse_env_iterations(Some(" 600000\n".to_owned())), 600000);
+        for value in ["", "0", "-1", "abc", "6e5", "99999999999"] {
+            assert_eq!(
+                parse_env_iterations(Some(value.to_owned())),
+                DEFAULT_ITERATIONS,
+                "{value:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_max_password_length() {
+        // the default limit, changing it would affect the other tests
+        let password = "a".repeat(MAX_PASSWORD_LENGTH);
+        let encoded_password = django_encode_password(&password, "seasalt", 1).unwrap();
+        assert!(django_auth(&password, &encoded_password).unwrap());
+
+        let password = "a".repeat(MAX_PASSWORD_LENGTH + 1);
+        let res = django_encode_password(&password, "seasalt", 1);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_allow_empty(&password, "seasalt", 1);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_auth(&password, &encoded_password);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn test_django_encode_password_salt_charset() {
+        for salt in [
+            "sea salt",
+            "sea\tsalt",
+            "seasalt\n",
+            "sea\0salt",
+            "sea-salt",
+            "sea$salt",
+            "s\u{e8}l",
+        ] {
+            let res = django_encode_password("hello", salt, 1000);
+            assert!(matches!(res, Err(Error::InvalidSalt(_))), "{salt:?}");
+            #[cfg(feature = "legacy-sha1")]
+            {
+                let res = django_encode_password_pbkdf2_sha1("hello", salt, 1000);
+                assert!(matches!(res, Err(Error::InvalidSalt(_))), "{salt:?}");
+            }
+            let res = django_encode_password_allow_empty("hello", salt, 1000);
+            assert!(matches!(res, Err(Error::InvalidSalt(_))), "{salt:?}");
+        }
+
+        // the hashers only reject the separator
+        let encoded_password = Pbkdf2Sha256Hasher { iterations: 1000 }
+            .encode("hello", "sea-salt")
+            .unwrap();
+        assert!(django_auth("hello", &encoded_password).unwrap());
+    }
+
+    #[test]
+    fn test_django_encode_password_hex() {
+        use base64::prelude::*;
+
+        let encoded_password = django_encode_password_hex("hello", "btQDcwXF2RoK6Q", 0).unwrap();
+        assert!(encoded_password.starts_with("pbkdf2_sha256_hex$180000$btQDcwXF2RoK6Q$"));
+        assert!(django_auth("hello", &encoded_password).unwrap());
+        assert!(!django_auth("world", &encoded_password).unwrap());
+
+        // the same hash as pbkdf2_sha256's, only encoded differently
+        let (_, hash) = encoded_password.rsplit_once('$').unwrap();
+        let digest = BASE64_STANDARD
+            .decode("D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=")
+            .unwrap();
+        let hex: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(hash, hex);
+
+        let res = django_encode_password_hex("", "seasalt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_hex("hello", "sea$salt", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+    }
+
+    #[test]
+    fn test_django_encode_password_with_salt_bytes() {
+        let salt: Vec<u8> = (0..=255).collect();
+        let encoded_password =
+            django_encode_password_with_salt_bytes("hello", &salt, 1000).unwrap();
+        assert!(encoded_password.starts_with("pbkdf2_sha256_b64salt$1000$AAECAwQF"));
+        assert!(django_auth("hello", &encoded_password).unwrap());
+        assert!(!django_auth("world", &encoded_password).unwrap());
+
+        // the same hash as pbkdf2_sha256's for a string salt
+        let encoded_password =
+            django_encode_password_with_salt_bytes("hello", "seasalt", 1000).unwrap();
+        let (_, hash) = encoded_password.rsplit_once('$').unwrap();
+        let expected = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(expected.ends_with(&format!("${hash}")));
+
+        let res = django_encode_password_with_salt_bytes("", b"seasalt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_with_salt_bytes("hello", b"", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+    }
+
+    #[test]
+    fn test_django_auth_urlencoded() {
+        let encoded_password =
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=";
+        let urlencoded =
+            "pbkdf2_sha256%241000%24seasalt%24RD2CKhnDcnVNzUV454538K%2Bx6R3OOcwZfjbISYPSaUI%3d";
+        assert!(django_auth_urlencoded("hello", urlencoded).unwrap());
+        assert!(!django_auth_urlencoded("world", urlencoded).unwrap());
+        // not encoded, or partially
+        assert!(django_auth_urlencoded("hello", encoded_password).unwrap());
+        assert!(django_auth_urlencoded(
+            "hello",
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K%2Bx6R3OOcwZfjbISYPSaUI="
+        )
+        .unwrap());
+        // django_auth doesn't decode
+        assert!(django_auth("hello", urlencoded).is_err());
+
+        assert!(!django_auth_urlencoded("hello", "%21").unwrap());
+        for urlencoded in [
+            "pbkdf2_sha256%241000%24seasalt%24RD2C%",
+            "pbkdf2_sha256%241000%24seasalt%24RD2C%2",
+            "pbkdf2_sha256%241000%24seasalt%24RD2C%zz",
+            "pbkdf2_sha256%241000%24seasalt%24RD2C%+1",
+            "pbkdf2_sha256%241000%24seasalt%24RD2C%ff",
+        ] {
+            let res = django_auth_urlencoded("hello", urlencoded);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{urlencoded}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_django_encode_password_escaped() {
+        // (base64.b64encode(hashlib.pbkdf2_hmac("sha256", b"hello", b"100%$", 1000)))
+        let encoded_password = django_encode_password_escaped("hello", "100%$", 1000).unwrap();
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$1000$100%25%24$B9Gd8TVcWHRY5dq3Tuo/Cll3P0mSyoINnV/zN1UUSaI="
+        );
+        assert!(django_auth_escaped("hello", &encoded_password).unwrap());
+        assert!(!django_auth_escaped("world", &encoded_password).unwrap());
+
+        let encoded_password = django_encode_password_escaped("hello", "sea$salt", 1000).unwrap();
+        assert!(django_auth_escaped("hello", &encoded_password).unwrap());
+
+        // Django's salts are not escaped
+        let encoded_password =
+            django_encode_password_escaped("hello", "btQDcwXF2RoK6Q", 0).unwrap();
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+        assert!(django_auth_escaped("hello", &encoded_password).unwrap());
+
+        let res = django_encode_password_escaped("hello", "", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+        let res = django_encode_password_escaped("", "sea$salt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+
+        let res = django_auth_escaped("hello", "pbkdf2_sha256$1000$sea%salt$wYBp");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        assert!(!django_auth_escaped("hello", "!").unwrap());
+    }
+
+    #[test]
+    fn test_django_encode_password_with_algorithm() {
+        let encoded_password = django_encode_password_with_algorithm(
+            Algorithm::Pbkdf2Sha256,
+            "hello",
+            "btQDcwXF2RoK6Q",
+        )
+        .unwrap();
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU="
+        );
+
+        #[cfg(feature = "legacy-sha1")]
+        {
+            let encoded_password = django_encode_password_with_algorithm(
+                Algorithm::Pbkdf2Sha1,
+                "hello",
+                "btQDcwXF2RoK6Q",
+            )
+            .unwrap();
+            assert_eq!(
+                encoded_password,
+                "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8="
+            );
+        }
+
+        let res = django_encode_password_with_algorithm(Algorithm::Pbkdf2Sha256, "", "seasalt");
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        #[cfg(feature = "argon2")]
+        {
+            let res = django_encode_password_with_algorithm(Algorithm::Argon2, "hello", "sea$salt");
+            assert!(matches!(res, Err(Error::InvalidSalt(_))));
+        }
+    }
+
+    #[cfg(feature = "argon2")]
+    #[test]
+    fn test_argon2_encode() {
+        let encoded_password = argon2_encode("l\u{e8}tmein", "abcdefghijk", 8, 1, 1).unwrap();
+        let hasher = Argon2Hasher {
+            memory_cost: 8,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        assert_eq!(
+            encoded_password,
+            hasher.encode("l\u{e8}tmein", "abcdefghijk").unwrap()
+        );
+        assert!(encoded_password.starts_with("argon2$argon2id$v=19$m=8,t=1,p=1$YWJjZGVmZ2hpams$"));
+        assert!(django_auth("l\u{e8}tmein", &encoded_password).unwrap());
+
+        // the hash is 32 bytes, without padding
+        let (_, hash) = encoded_password.rsplit_once('$').unwrap();
+        assert_eq!(hash.len(), 43);
+
+        let res = argon2_encode("", "abcdefghijk", 8, 1, 1);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = argon2_encode("secret", "abc", 8, 1, 1);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))), "{res:?}");
+        // too little memory for 2 lanes, no iterations
+        for (memory_kib, time_cost, parallelism) in [(8, 1, 2), (8, 0, 1)] {
+            let res = argon2_encode("secret", "abcdefghijk", memory_kib, time_cost, parallelism);
+            assert!(
+                matches!(res, Err(Error::InvalidIterations(_))),
+                "{memory_kib} {time_cost} {parallelism}: {res:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_wrap_hash() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let wrapped = wrap_hash(&encoded_password, "pepper", 1000).unwrap();
+        assert!(django_auth("hello", &wrapped).unwrap());
+        assert_eq!(
+            verify("hello", &wrapped).unwrap(),
+            Verification::ValidButNeedsRehash
+        );
+        assert_eq!(verify("world", &wrapped).unwrap(), Verification::Invalid);
+
+        let res = wrap_hash(&encoded_password, "pep$per", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+        let res = wrap_hash(&encoded_password, "", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+        let res = wrap_hash("!", "pepper", 1000);
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = wrap_hash(&encoded_password, "pepper", MAX_ENCODE_ITERATIONS + 1);
+        assert!(matches!(res, Err(Error::InvalidIterations(_))));
+    }
+
+    #[test]
+    fn test_max_encoded_len() {
+        let encoded_password =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert_eq!(
+            max_encoded_len("pbkdf2_sha256", 180000, 14).unwrap(),
+            encoded_password.len()
+        );
+        assert_eq!(max_encoded_len("pbkdf2_sha256", 0, 14).unwrap(), 80);
+
+        for (iterations, salt) in [(1, "a"), (9, "seasalt"), (10, "seasalt"), (1000, "")] {
+            let encoded_password = Pbkdf2Sha256Hasher { iterations }
+                .encode("hello", salt)
+                .unwrap();
+            assert_eq!(
+                max_encoded_len("pbkdf2_sha256", iterations, salt.len()).unwrap(),
+                encoded_password.len(),
+                "{encoded_password}"
+            );
+            let encoded_password = Pbkdf2Sha512Hasher { iterations }
+                .encode("hello", salt)
+                .unwrap();
+            assert_eq!(
+                max_encoded_len("pbkdf2_sha512", iterations, salt.len()).unwrap(),
+                encoded_password.len(),
+                "{encoded_password}"
+            );
+            let encoded_password = Pbkdf2Sha256HexHasher { iterations }
+                .encode("hello", salt)
+                .unwrap();
+            assert_eq!(
+                max_encoded_len("pbkdf2_sha256_hex", iterations, salt.len()).unwrap(),
+                encoded_password.len(),
+                "{encoded_password}"
+            );
+        }
+        assert_eq!(max_encoded_len("pbkdf2_sha256", u32::MAX, 22).unwrap(), 92);
+        assert_eq!(max_encoded_len("pbkdf2_sha1", 180000, 14).unwrap(), 62);
+        let encoded_password = "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0afb1e91dce39cc197e36c84983d26942";
+        assert_eq!(
+            max_encoded_len("pbkdf2_sha256_hex", 1000, 7).unwrap(),
+            encoded_password.len()
+        );
+        validate_hash_length(encoded_password).unwrap();
+        let encoded_password =
+            "pbkdf2_sha256_b64salt$1000$c2Vhc2FsdA==$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=";
+        assert_eq!(
+            max_encoded_len("pbkdf2_sha256_b64salt", 1000, 7).unwrap(),
+            encoded_password.len()
+        );
+        for salt_len in [1, 2, 3, 16] {
+            let salt: Vec<u8> = (0..salt_len).collect();
+            let encoded_password =
+                django_encode_password_with_salt_bytes("hello", &salt, 1000).unwrap();
+            assert_eq!(
+                max_encoded_len("pbkdf2_sha256_b64salt", 1000, salt.len()).unwrap(),
+                encoded_password.len(),
+                "{encoded_password}"
+            );
+        }
+
+        let res = max_encoded_len("argon2", 0, 22);
+        assert_eq!(res, Err(Error::UnsupportedAlgorithm("argon2".to_owned())));
+    }
+
+    #[test]
+    fn test_max_encode_iterations() {
+        assert_eq!(max_encode_iterations(), MAX_ENCODE_ITERATIONS);
+
+        // rejected before hashing
+        for iterations in [MAX_ENCODE_ITERATIONS + 1, u32::MAX] {
+            let res = django_encode_password("hello", "seasalt", iterations);
+            assert_eq!(
+                res,
+                Err(Error::InvalidIterations(format!(
+                    "pbkdf2_sha256 iterations should not be more than 10000000, got {iterations}"
+                )))
+            );
+            let res = Pbkdf2Sha256Hasher { iterations }.encode("hello", "seasalt");
+            assert!(matches!(res, Err(Error::InvalidIterations(_))));
+            let res = Pbkdf2Sha256HexHasher { iterations }.encode("hello", "seasalt");
+            assert!(matches!(res, Err(Error::InvalidIterations(_))));
+            let res = django_encode_password_escaped("hello", "sea$salt", iterations);
+            assert!(matches!(res, Err(Error::InvalidIterations(_))));
+            let res = derive_key("hello", "seasalt", iterations, 32);
+            assert!(matches!(res, Err(Error::InvalidIterations(_))));
+            let res = encode_params("seasalt", iterations);
+            assert!(matches!(res, Err(Error::InvalidIterations(_))));
+        }
+    }
+
+    #[test]
+    fn test_encode_params() {
+        assert_eq!(
+            encode_params("seasalt", 1000).unwrap(),
+            ("pbkdf2_sha256".to_owned(), 1000, "seasalt".to_owned())
+        );
+        assert_eq!(encode_params("seasalt", 0).unwrap().1, default_iterations());
+
+        // the parameters the encoded password is made of
+        let (algorithm, iterations, salt) = encode_params("seasalt", 1000).unwrap();
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(encoded_password.starts_with(&format!("{algorithm}${iterations}${salt}$")));
+
+        // and the same salt validation
+        for salt in ["", "sea$salt", "sea salt", "sèasalt"] {
+            assert!(
+                matches!(encode_params(salt, 1000), Err(Error::InvalidSalt(_))),
+                "{salt:?}"
+            );
+            assert!(django_encode_password("hello", salt, 1000).is_err());
+        }
+    }
+
+    #[test]
+    fn test_derive_key() {
+        use base64::prelude::*;
+
+        let hex = |key: Vec<u8>| -> String { key.iter().map(|b| format!("{b:02x}")).collect() };
+
+        // RFC 7914 section 11
+        assert_eq!(
+            hex(derive_key("passwd", "salt", 1, 64).unwrap()),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+        assert_eq!(
+            hex(derive_key("Password", "NaCl", 80000, 64).unwrap()),
+            "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+             a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d"
+        );
+        // hashlib.pbkdf2_hmac("sha256", b"hello", b"seasalt", 1000, 10)
+        assert_eq!(
+            hex(derive_key("hello", "seasalt", 1000, 10).unwrap()),
+            "443d822a19c372754dcd"
+        );
+
+        // the hash of django_encode_password
+        let key = derive_key("hello", "seasalt", 1000, 32).unwrap();
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert_eq!(
+            encoded_password,
+            format_encoded(
+                "pbkdf2_sha256",
+                1000,
+                "seasalt",
+                &BASE64_STANDARD.encode(key)
+            )
+        );
+
+        assert_eq!(derive_key("", "", 1, 1).unwrap().len(), 1);
+        let key = derive_key("hello", "seasalt", 1, MAX_DERIVED_KEY_LEN).unwrap();
+        assert_eq!(key.len(), MAX_DERIVED_KEY_LEN);
+        for len in [0, MAX_DERIVED_KEY_LEN + 1, usize::MAX] {
+            let res = derive_key("hello", "seasalt", 1, len);
+            assert!(matches!(res, Err(Error::InvalidLength(_))), "{len}");
+        }
+        let long = "a".repeat(max_password_length() + 1);
+        let res = derive_key(&long, "seasalt", 1, 32);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn test_as_ref_inputs() {
+        use alloc::borrow::Cow;
+
+        let password = String::from("hello");
+        let salt: Cow<str> = Cow::Borrowed("seasalt");
+        let encoded_password = django_encode_password(&password, salt, 1000).unwrap();
+        let from_bytes = django_encode_password_bytes(Vec::from("hello"), "seasalt", 1000);
+        assert_eq!(from_bytes.unwrap(), encoded_password);
+
+        assert!(django_auth(&password, &encoded_password).unwrap());
+        assert!(django_auth(password.clone(), encoded_password.clone()).unwrap());
+        let owned: Cow<str> = Cow::Owned(encoded_password.clone());
+        assert!(check_password("hello", owned).unwrap());
+        assert!(django_auth_bytes(b"hello", &encoded_password).unwrap());
+        assert!(django_auth_bytes(vec![b'h'; 3], &encoded_password).is_ok());
+        assert_eq!(get_iterations(&encoded_password).unwrap(), 1000);
+        assert!(is_password_usable(encoded_password));
+    }
+
+    #[test]
+    fn test_django_auth_whitespace() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(!encoded_password.contains(char::is_whitespace));
+
+        for padded in [
+            format!("{encoded_password}\n"),
+            format!("{encoded_password}\r\n"),
+            format!(" \t{encoded_password} "),
+        ] {
+            assert!(django_auth("hello", &padded).unwrap(), "{padded:?}");
+            assert!(!django_auth("world", &padded).unwrap(), "{padded:?}");
+            assert_eq!(
+                verify("hello", &padded).unwrap(),
+                Verification::ValidButNeedsRehash
+            );
+        }
+        assert!(!django_auth("hello", " !\n").unwrap());
+
+        // the other functions parsing an encoded password ignore it too
+        for padded in [
+            format!("{encoded_password}\n"),
+            format!(" {encoded_password}"),
+        ] {
+            assert!(
+                django_auth_with_policy("hello", &padded, &["pbkdf2_sha256"]).unwrap(),
+                "{padded:?}"
+            );
+            assert!(django_auth_expect("hello", &padded, Algorithm::Pbkdf2Sha256).unwrap());
+            assert!(django_auth_with_min_salt_len("hello", &padded, 7).unwrap());
+            assert!(django_auth_escaped("hello", &padded).unwrap());
+            assert_eq!(
+                verify_candidates(&["world", "hello"], &padded).unwrap(),
+                Some(1)
+            );
+            assert!(encoded_eq(&padded, &encoded_password).unwrap());
+            assert!(encoded_eq(&encoded_password, &padded).unwrap());
+            validate_hash_length(&padded).unwrap();
+            assert_eq!(get_salt(&padded).unwrap(), "seasalt");
+            assert_eq!(get_iterations(&padded).unwrap(), 1000);
+            assert!(is_pbkdf2(&padded));
+            assert!(needs_update(&padded, 2000).unwrap());
+            assert!(HasherRegistry::default().must_update(&padded).unwrap());
+            assert!(!HasherRegistry::default().must_update(" !\n").unwrap());
+        }
+
+        // only around the encoded password
+        let (head, tail) = encoded_password.split_at(20);
+        let res = django_auth("hello", format!("{head} {tail}"));
+        assert!(!matches!(res, Ok(true)));
+    }
+
+    #[test]
+    fn test_django_auth_constant() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(django_auth_constant("hello", &encoded_password));
+        assert!(!django_auth_constant("world", &encoded_password));
+        assert!(!django_auth_constant("", &encoded_password));
+        // surrounding whitespace is ignored
+        assert!(django_auth_constant(
+            "hello",
+            format!(" {encoded_password} \n")
+        ));
+        assert!(!django_auth_constant("hello", " !abc"));
+
+        let long = "a".repeat(max_password_length() + 1);
+        for (password, encoded_password) in [
+            ("hello", "garbage"),
+            ("hello", "pbkdf2_sha256$1000$seasalt$not base64!"),
+            ("hello", "pbkdf2_sha256$abc$seasalt$hash"),
+            ("hello", "foo$salt$hash"),
+            ("hello", "!"),
+            ("hello", ""),
+            (&long, &encoded_password),
+        ] {
+            assert!(
+                !django_auth_constant(password, encoded_password),
+                "{encoded_password}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_django_auth_bytes() {
+        let encoded_password = django_encode_password_bytes("hello".as_bytes(), "seasalt", 1000)
+            .expect("django_encode_password_bytes failed");
+        assert_eq!(
+            encoded_password,
+            django_encode_password("hello", "seasalt", 1000).unwrap()
+        );
+        assert!(django_auth_bytes(b"hello", &encoded_password).unwrap());
+
+        // passwords don't have to be valid UTF-8
+        let password = b"l\xe8tmein";
+        let encoded_password = django_encode_password_bytes(password, "seasalt", 1000)
+            .expect("django_encode_password_bytes failed");
+        assert!(django_auth_bytes(password, &encoded_password).unwrap());
+        assert!(!django_auth_bytes(b"letmein", &encoded_password).unwrap());
+        assert!(!django_auth("l\u{e8}tmein", &encoded_password).unwrap());
+    }
+
+    #[test]
+    fn test_django_auth_batch() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let pairs: Vec<(String, String)> = (0..16)
+            .map(|i| {
+                let password = if i % 3 == 0 { "hello" } else { "world" };
+                (password.to_owned(), encoded_password.clone())
+            })
+            .chain([("hello".to_owned(), "abc$edf".to_owned())])
+            .collect();
+
+        let res = django_auth_batch(&pairs);
+        assert_eq!(res.len(), pairs.len());
+        for (i, res) in res[..16].iter().enumerate() {
+            assert_eq!(*res.as_ref().unwrap(), i % 3 == 0);
+        }
+        assert!(res[16].is_err());
+
+        assert!(django_auth_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_django_auth_iter() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let rows = [
+            ("hello", encoded_password.as_str()),
+            ("world", encoded_password.as_str()),
+            ("hello", "abc$edf"),
+            ("hello", "!"),
+            ("hello", encoded_password.as_str()),
+        ];
+
+        // nothing is verified until pulled
+        let mut verified = 0;
+        let mut res = django_auth_iter(rows.iter().inspect(|_| verified += 1).copied());
+        assert!(res.next().unwrap().unwrap());
+        assert!(!res.next().unwrap().unwrap());
+        drop(res);
+        assert_eq!(verified, 2);
+
+        // an error doesn't stop the iteration
+        let res: Vec<Result<bool>> = django_auth_iter(rows).collect();
+        assert_eq!(res.len(), rows.len());
+        assert!(res[2].is_err());
+        assert_eq!(res[3], Ok(false));
+        assert_eq!(res[4], Ok(true));
+
+        // owned pairs
+        let owned = vec![("hello".to_owned(), encoded_password.clone())];
+        assert_eq!(django_auth_iter(owned).collect::<Vec<_>>(), [Ok(true)]);
+        assert_eq!(django_auth_iter::<&str, &str>([]).count(), 0);
+    }
+
+    #[test]
+    fn test_verify_candidates() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let candidates = ["123456", "password", "hello", "hello!", "hello"];
+        assert_eq!(
+            verify_candidates(&candidates, &encoded_password).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            verify_candidates(&candidates[3..], &encoded_password).unwrap(),
+            Some(1)
+        );
+        assert_eq!(
+            verify_candidates(&candidates[..2], &encoded_password).unwrap(),
+            None
+        );
+        assert_eq!(verify_candidates(&[], &encoded_password).unwrap(), None);
+
+        #[cfg(feature = "argon2")]
+        {
+            let hasher = Argon2Hasher {
+                memory_cost: 8,
+                time_cost: 1,
+                parallelism: 1,
+            };
+            let encoded_password = hasher.encode("hello", "seasaltseasalt").unwrap();
+            let res = verify_candidates(&["password", "hello"], &encoded_password);
+            assert_eq!(res.unwrap(), Some(1));
+            let res = verify_candidates(&["password"], &encoded_password);
+            assert_eq!(res.unwrap(), None);
+        }
+
+        // every pbkdf2 variant
+        for encoded_password in [
+            "pbkdf2_sha512$1000$seasalt$9EmDloRvwcysQnIq3lo8Mzne3FaccLadeTaYarxzxpZNs2P3uy7gMkPw0k60qPWt7i5nMkjW6Ut6jR/sBTnHyQ==",
+            "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0afb1e91dce39cc197e36c84983d26942",
+            "pbkdf2_sha256_b64salt$1000$c2Vhc2FsdA==$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+        ] {
+            let res = verify_candidates(&candidates, encoded_password);
+            assert_eq!(res.unwrap(), Some(2), "{encoded_password}");
+            let res = verify_candidates(&candidates[..2], encoded_password);
+            assert_eq!(res.unwrap(), None, "{encoded_password}");
+        }
+        #[cfg(feature = "legacy-sha1")]
+        {
+            let encoded_password =
+                django_encode_password_pbkdf2_sha1("hello", "seasalt", 1000).unwrap();
+            let res = verify_candidates(&candidates, &encoded_password);
+            assert_eq!(res.unwrap(), Some(2));
+            let res = verify_candidates(&candidates[..2], &encoded_password);
+            assert_eq!(res.unwrap(), None);
+        }
+
+        assert_eq!(verify_candidates(&candidates, "!").unwrap(), None);
+        let res = verify_candidates(&candidates, "pbkdf2_sha256$1000$seasalt$not base64!");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = verify_candidates(&candidates, "foo$salt$hash");
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+        let long = "a".repeat(max_password_length() + 1);
+        let res = verify_candidates(&["hello", &long], &encoded_password);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+    }
+
+    #[cfg(feature = "legacy-sha1")]
+    #[test]
+    fn test_django_encode_password_pbkdf2_sha1() {
+        let password = "hello";
+        let encoded_password = django_encode_password_pbkdf2_sha1(password, "btQDcwXF2RoK6Q", 0)
+            .expect("django_encode_password_pbkdf2_sha1 failed");
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8="
+        );
+        assert!(django_auth(password, &encoded_password).unwrap());
+        assert!(!django_auth("world", &encoded_password).unwrap());
+
+        let encoded_password = django_encode_password_pbkdf2_sha1(password, "seasalt", 1000)
+            .expect("django_encode_password_pbkdf2_sha1 failed");
+        assert!(encoded_password.starts_with("pbkdf2_sha1$1000$seasalt$"));
+        assert!(django_auth(password, &encoded_password).unwrap());
+
+        let res = django_encode_password_pbkdf2_sha1(password, "btQDcwXF$2RoK6Q", 0);
+        assert!(res.is_err());
+
+        let res = django_encode_password_pbkdf2_sha1("", "seasalt", 1000);
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn test_supported_algorithms() {
+        let supported = supported_algorithms();
+        assert_eq!(supported[0], "pbkdf2_sha256");
+        assert_eq!(supported.contains(&"argon2"), cfg!(feature = "argon2"));
+        assert_eq!(
+            supported.contains(&"md5"),
+            cfg!(feature = "insecure-hashers")
+        );
+
+        let registry = HasherRegistry::default();
+        for algorithm in Algorithm::ALL {
+            assert_eq!(
+                supported.contains(&algorithm.as_str()),
+                registry.get(algorithm.as_str()).is_some(),
+                "{algorithm}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_identify_hasher() {
+        let encoded =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert_eq!(identify_hasher(encoded).unwrap(), "pbkdf2_sha256");
+
+        let encoded = "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy";
+        assert_eq!(identify_hasher(encoded).unwrap(), "bcrypt_sha256");
+
+        assert_eq!(identify_hasher("foo$bar").unwrap(), "foo");
+
+        #[cfg(not(feature = "insecure-hashers"))]
+        assert!(matches!(
+            identify_hasher("5f4dcc3b5aa765d61d8327deb882cf99"),
+            Err(Error::InvalidEncodedPassword(_))
+        ));
+        #[cfg(feature = "insecure-hashers")]
+        assert_eq!(
+            identify_hasher("5f4dcc3b5aa765d61d8327deb882cf99").unwrap(),
+            "unsalted_md5"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_make_password() {
+        let encoded_password = make_password("hello").expect("make_password failed");
+        let parts: Vec<&str> = encoded_password.split('$').collect();
+        assert_eq!(parts.len(), 4);
+        assert_eq!(parts[0], "pbkdf2_sha256");
+        assert_eq!(parts[1], DEFAULT_ITERATIONS.to_string());
+        assert_eq!(parts[2].len(), 12);
+        assert!(parts[2].chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(django_auth("hello", &encoded_password).unwrap());
+
+        // salts are random
+        assert_ne!(make_password("hello").unwrap(), encoded_password);
+
+        assert!(matches!(make_password(""), Err(Error::InvalidPassword(_))));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_generate_salt() {
+        let salt = generate_salt();
+        assert_eq!(salt.len(), 12);
+        assert!(salt.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_ne!(generate_salt(), salt);
+
+        for len in [0, 1, 22, 100] {
+            let salt = generate_salt_with_len(len);
+            assert_eq!(salt.len(), len);
+            assert!(salt.chars().all(|c| c.is_ascii_alphanumeric()));
+        }
+    }
+
+    #[test]
+    fn test_generate_salt_with_rng() {
+        use rand::rngs::mock::StepRng;
+
+        // each step draws the next character of the charset
+        let mut rng = StepRng::new(0, 1 << 26);
+        assert_eq!(generate_salt_with_rng(&mut rng, 12), "ABCDEFGHIJKL");
+        assert_eq!(generate_salt_with_rng(&mut rng, 3), "MNO");
+        assert_eq!(generate_salt_with_rng(&mut rng, 0), "");
+
+        let encoded_password =
+            make_password_with_rng("hello", &mut StepRng::new(0, 1 << 26)).unwrap();
+        assert_eq!(
+            encoded_password,
+            Pbkdf2Sha256::default()
+                .salt("ABCDEFGHIJKL")
+                .encode("hello")
+                .unwrap()
+        );
+        assert!(django_auth("hello", &encoded_password).unwrap());
+        assert!(make_password_with_rng("", &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_encoded_eq() {
+        let a = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(encoded_eq(&a, a.clone()).unwrap());
+        let (prefix, hash) = a.rsplit_once('$').unwrap();
+        assert!(encoded_eq(&a, format!("pbkdf2_sha256$01000$seasalt${hash}")).unwrap());
+
+        for b in [
+            django_encode_password("hello", "pepper", 1000).unwrap(),
+            django_encode_password("hello", "seasalt", 1001).unwrap(),
+            django_encode_password("world", "seasalt", 1000).unwrap(),
+            format!("pbkdf2_sha1$1000$seasalt${hash}"),
+            format!("{prefix}$RD2CKhnDcnVNzUV454538A=="),
+            format!("!{a}"),
+        ] {
+            assert!(!encoded_eq(&a, &b).unwrap(), "{b}");
+            assert!(!encoded_eq(&b, &a).unwrap(), "{b}");
+        }
+
+        let bcrypt = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza";
+        assert!(encoded_eq(bcrypt, bcrypt).unwrap());
+        let other = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNzb";
+        assert!(!encoded_eq(bcrypt, other).unwrap());
+        assert!(!encoded_eq(bcrypt, &a).unwrap());
+        let argon2 = "argon2$argon2i$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q";
+        assert!(encoded_eq(argon2, argon2).unwrap());
+        assert!(!encoded_eq(
+            argon2,
+            "argon2$argon2i$v=19$m=8,t=1,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q$"
+        )
+        .unwrap());
+
+        assert!(encoded_eq(&a, "abc").is_err());
+        assert!(encoded_eq("pbkdf2_sha256$abc$salt$hash", "pbkdf2_sha256$abc$salt$hash").is_err());
+    }
+
+    #[test]
+    fn test_format_encoded() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let (_, hash) = encoded_password.rsplit_once('$').unwrap();
+        assert_eq!(
+            format_encoded("pbkdf2_sha256", 1000, "seasalt", hash),
+            encoded_password
+        );
+
+        let encoded: EncodedPassword = encoded_password.parse().unwrap();
+        assert_eq!(
+            format_encoded("pbkdf2_sha256", 1000, "seasalt", &encoded.hash),
+            encoded.to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_hash_length() {
+        for encoded in [
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+            // 64 hex characters
+            "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0afb1e91dce39cc197e36c84983d26942",
+        ] {
+            validate_hash_length(encoded).unwrap();
+        }
+
+        for encoded in [
+            // too short (16 bytes)
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538A==",
+            // too long (64 bytes)
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUKsM9/W9aH/QBc5a83zjwWQhGwf4Rnt3amoCXgRy8KBuA==",
+            // a pbkdf2_sha256 hash (32 bytes)
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha256$1000$seasalt$not base64!",
+            "pbkdf2_sha256$1000$seasalt$",
+            // 16 bytes
+            "pbkdf2_sha256_hex$1000$seasalt$443d822a19c372754dcd4578e78e77f0",
+            // base64
+            "pbkdf2_sha256_hex$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUI=",
+        ] {
+            let res = validate_hash_length(encoded);
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))), "{encoded}");
+        }
+
+        // verification rejects the lengths it can't verify safely
+        assert!(django_auth(
+            "hello",
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538A=="
+        )
+        .unwrap());
+        for encoded in [
+            // too short (15 bytes)
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV45453",
+            // too long (64 bytes)
+            "pbkdf2_sha256$1000$seasalt$RD2CKhnDcnVNzUV454538K+x6R3OOcwZfjbISYPSaUKsM9/W9aH/QBc5a83zjwWQhGwf4Rnt3amoCXgRy8KBuA==",
+        ] {
+            let res = django_auth("hello", encoded);
+            assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))), "{encoded}");
+        }
+
+        let res = validate_hash_length(
+            "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+        );
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_get_iterations() {
+        let encoded =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert_eq!(get_iterations(encoded).unwrap(), 180000);
+        let encoded = "pbkdf2_sha1$1000$salt$hash";
+        assert_eq!(get_iterations(encoded).unwrap(), 1000);
+
+        let res = get_iterations("pbkdf2_sha256$abc$salt$hash");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res =
+            get_iterations("bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza");
+        assert!(matches!(res, Err(Error::InvalidEncodedPassword(_))));
+        let res = get_iterations("foo$180000$salt$hash");
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+
+        // the salt of the algorithms without iterations is never parsed as one
+        for encoded in [
+            "sha1$180000$cff36ea83f5706ce9aa7454e63e431fc726b2dc8",
+            "sha1$180000$salt$hash",
+            "md5$1000$3f86d0d3d465b7b458c231bf3555c0e3",
+            "sha1$$6d138ca3ae545631b3abd71a4f076ce759c5700b",
+            "argon2$argon2id$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+            "scrypt$1024$seasalt$8$1$+qO2jTkVUbPNlniTkHY96g==",
+            "bcrypt_sha256$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuXycFwEHjpWlx10LDgON9B7k6MWMkXqy",
+        ] {
+            match get_iterations(encoded) {
+                Err(Error::InvalidEncodedPassword(msg)) => {
+                    assert!(
+                        msg.ends_with("encoded passwords have no iterations"),
+                        "{msg}"
+                    )
+                }
+                res => panic!("{encoded}: {res:?}"),
+            }
+        }
+
+        // pbkdf2 without iterations
+        for encoded in [
+            "pbkdf2_sha256$salt$hash",
+            "pbkdf2_sha1$$hash",
+            "pbkdf2_sha1",
+        ] {
+            let res = get_iterations(encoded);
+            assert!(
+                matches!(res, Err(Error::InvalidEncodedPassword(_))),
+                "{encoded}"
+            );
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_upgrade_if_needed() {
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let upgraded = upgrade_if_needed("hello", &encoded_password, 2000)
+            .unwrap()
+            .unwrap();
+        assert!(upgraded.starts_with("pbkdf2_sha256$2000$"));
+        assert!(!upgraded.starts_with("pbkdf2_sha256$2000$seasalt$"));
+        assert!(django_auth("hello", &upgraded).unwrap());
+        assert!(upgrade_if_needed("hello", &upgraded, 2000)
+            .unwrap()
+            .is_none());
+        assert!(upgrade_if_needed("hello", &upgraded, 1000)
+            .unwrap()
+            .is_none());
+
+        let res = upgrade_if_needed("world", &encoded_password, 2000);
+        assert!(matches!(res, Err(Error::IncorrectPassword)));
+        let res = upgrade_if_needed("hello", "!", 2000);
+        assert!(matches!(res, Err(Error::IncorrectPassword)));
+        let res = upgrade_if_needed("hello", "abc$edf", 2000);
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+
+        // the other pbkdf2 algorithms
+        let encoded_password = "pbkdf2_sha512$1000$seasalt$9EmDloRvwcysQnIq3lo8Mzne3FaccLadeTaYarxzxpZNs2P3uy7gMkPw0k60qPWt7i5nMkjW6Ut6jR/sBTnHyQ==";
+        let upgraded = upgrade_if_needed("hello", encoded_password, 2000)
+            .unwrap()
+            .unwrap();
+        assert!(upgraded.starts_with("pbkdf2_sha256$2000$"));
+        assert!(upgrade_if_needed("hello", encoded_password, 1000)
+            .unwrap()
+            .is_none());
+        assert!(django_auth_with_min_salt_len("hello", encoded_password, 7).unwrap());
+    }
+
+    #[test]
+    fn test_migrate_hash() {
+        let old_encoded = "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3";
+        #[cfg(feature = "insecure-hashers")]
+        {
+            let new_encoded = migrate_hash("l\u{e8}tmein", old_encoded, "pepper", 1000)
+                .unwrap()
+                .unwrap();
+            assert_eq!(
+                new_encoded,
+                django_encode_password("l\u{e8}tmein", "pepper", 1000).unwrap()
+            );
+            assert!(migrate_hash("letmein", old_encoded, "pepper", 1000)
+                .unwrap()
+                .is_none());
+
+            let old_encoded = "88a434c88cca4e900f7874cd98123f43";
+            assert!(migrate_hash("l\u{e8}tmein", old_encoded, "pepper", 1000)
+                .unwrap()
+                .is_some());
+        }
+        #[cfg(not(feature = "insecure-hashers"))]
+        {
+            let res = migrate_hash("l\u{e8}tmein", old_encoded, "pepper", 1000);
+            assert!(matches!(res, Err(Error::FeatureNotEnabled(_))));
+        }
+
+        // the salt is checked first
+        let res = migrate_hash("l\u{e8}tmein", old_encoded, "pep$per", 1000);
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        let new_encoded = migrate_hash("hello", &encoded_password, "pepper", 2000)
+            .unwrap()
+            .unwrap();
+        assert!(new_encoded.starts_with("pbkdf2_sha256$2000$pepper$"));
+        assert!(migrate_hash("hello", "!", "pepper", 2000)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_is_pbkdf2() {
+        for encoded_password in [
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+            "pbkdf2_sha512$180000$salt$hash",
+            "pbkdf2_sha256_hex$1000$seasalt$443d822a",
+            "pbkdf2_sha256_b64salt$1000$c2Vhc2FsdA==$hash",
+        ] {
+            assert!(is_pbkdf2(encoded_password), "{encoded_password}");
+        }
+        for encoded_password in [
+            "argon2$argon2id$v=19$m=8,t=2,p=1$c2FsdHNhbHQ$YC9+jJCrQhs5R6db7LlN8Q",
+            "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza",
+            "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3",
+            "!pbkdf2_sha256$180000$btQDcwXF2RoK6Q$hash",
+            "pbkdf2_sha256$abc$salt$hash",
+            "pbkdf2_sha256$180000$salt",
+            "pbkdf2_sha256",
+            "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$hash",
+            "pbkdf2_sha384$180000$salt$hash",
+            "",
+        ] {
+            assert!(!is_pbkdf2(encoded_password), "{encoded_password}");
+        }
+    }
+
+    #[test]
+    fn test_needs_update() {
+        let encoded =
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=";
+        assert!(needs_update(encoded, 180001).unwrap());
+        assert!(!needs_update(encoded, 180000).unwrap());
+        assert!(!needs_update(encoded, 100000).unwrap());
+
+        let encoded = "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=";
+        assert!(needs_update(encoded, 600000).unwrap());
+
+        let encoded = "bcrypt$$2b$04$Ju3n0NxMoXrbC2LMcRKDXuJIiQBXpOeFOgzoEfIHtmQ0sfnk3gNza";
+        assert!(!needs_update(encoded, 600000).unwrap());
+
+        assert!(!needs_update("!sYJeAxvZgiZ3oQDUaSGMBLwqUAbDHxF8XAsP7tj5", 600000).unwrap());
+        assert!(needs_update("pbkdf2_sha256$abc$salt$hash", 600000).is_err());
+        assert!(needs_update("foo$180000$salt$hash", 600000).is_err());
+
+        for encoded in [
+            "pbkdf2_sha512$180000$salt$hash",
+            "pbkdf2_sha256_hex$180000$salt$hash",
+            "pbkdf2_sha256_b64salt$180000$c2FsdA==$hash",
+        ] {
+            assert_eq!(get_iterations(encoded).unwrap(), 180000, "{encoded}");
+            assert!(needs_update(encoded, 600000).unwrap(), "{encoded}");
+            assert!(!needs_update(encoded, 180000).unwrap(), "{encoded}");
+        }
+        let encoded = "pbkdf2_wrapped$pbkdf2_sha256$1000$seasalt$1000$pepper$hash";
+        assert!(!needs_update(encoded, 600000).unwrap());
     }
 }
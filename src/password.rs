@@ -0,0 +1,101 @@
+use alloc::string::String;
+use core::fmt;
+
+use zeroize::Zeroizing;
+
+/// A plaintext password, which is printed as `***` by `Debug` and `Display`
+/// so that it can't leak into log lines or panic messages, and zeroized on
+/// drop.
+///
+/// The functions taking a password accept it, as they take any `AsRef<str>`.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let password = Password::from("hello");
+/// assert_eq!(format!("{password:?} {password}"), "*** ***");
+///
+/// let res = django_auth(
+///     &password,
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// )
+/// .expect("django_auth error");
+/// assert!(res);
+/// ```
+///
+#[derive(Clone, Default)]
+pub struct Password(Zeroizing<String>);
+
+impl Password {
+    /// The plaintext password.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Password {
+    fn from(password: &str) -> Self {
+        Self(Zeroizing::new(password.into()))
+    }
+}
+
+impl From<String> for Password {
+    fn from(password: String) -> Self {
+        Self(Zeroizing::new(password))
+    }
+}
+
+impl AsRef<str> for Password {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<[u8]> for Password {
+    fn as_ref(&self) -> &[u8] {
+        self.as_str().as_bytes()
+    }
+}
+
+impl fmt::Debug for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("***")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use crate::{django_auth, django_auth_bytes, django_encode_password};
+
+    #[test]
+    fn test_password() {
+        let password = Password::from("hello");
+        assert_eq!(password.as_str(), "hello");
+        assert_eq!(format!("{password:?}"), "***");
+        assert_eq!(password.to_string(), "***");
+        // nested in other types
+        assert_eq!(
+            format!("{:?}", Some(("alice", &password))),
+            "Some((\"alice\", ***))"
+        );
+
+        let encoded_password = django_encode_password(&password, "seasalt", 1000).unwrap();
+        assert!(django_auth(&password, &encoded_password).unwrap());
+        assert!(django_auth_bytes(&password, &encoded_password).unwrap());
+        assert!(!django_auth(Password::from("world".to_string()), &encoded_password).unwrap());
+
+        assert_eq!(Password::default().as_str(), "");
+    }
+}
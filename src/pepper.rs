@@ -0,0 +1,127 @@
+use alloc::borrow::ToOwned;
+use alloc::string::String;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::{
+    check_encode_password, check_encode_salt, check_password_length, django_auth_bytes,
+    django_encode_password_bytes, Error, Result,
+};
+
+/// Same as [crate::django_encode_password], but the password is first mixed
+/// with a server-side secret `pepper`, as `HMAC-SHA256(pepper, password)`, so
+/// that the encoded passwords can't be cracked without the pepper, e.g. from a
+/// leaked database alone.
+///
+/// The encoded password looks like any pbkdf2_sha256 one, but it can only be
+/// verified with [django_auth_with_pepper] and the same pepper: **Django
+/// can't verify it**, unless it uses a custom hasher implementing the same
+/// scheme. Changing the pepper invalidates all the encoded passwords.
+///
+/// Requires the `pepper` feature.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let encoded_password =
+///     django_encode_password_with_pepper("hello", "seasalt", 1000, b"serversecret")
+///         .expect("django_encode_password_with_pepper error");
+///
+/// assert!(django_auth_with_pepper("hello", &encoded_password, b"serversecret")
+///     .expect("django_auth_with_pepper error"));
+/// assert!(!django_auth("hello", &encoded_password).expect("django_auth error"));
+/// ```
+///
+pub fn django_encode_password_with_pepper(
+    password: impl AsRef<str>,
+    salt: impl AsRef<str>,
+    iterations: u32,
+    pepper: impl AsRef<[u8]>,
+) -> Result<String> {
+    let password = password.as_ref();
+    let salt = salt.as_ref();
+    let pepper = pepper.as_ref();
+    check_encode_password(password.as_bytes())?;
+    check_encode_salt(salt)?;
+    let peppered = pepper_password(password.as_bytes(), pepper)?;
+    django_encode_password_bytes(peppered.as_ref(), salt, iterations)
+}
+
+/// Verify `password` against an `encoded_password` encoded by
+/// [django_encode_password_with_pepper] with the same `pepper`, like
+/// [crate::django_auth] does.
+///
+/// Requires the `pepper` feature.
+pub fn django_auth_with_pepper(
+    password: impl AsRef<str>,
+    encoded_password: impl AsRef<str>,
+    pepper: impl AsRef<[u8]>,
+) -> Result<bool> {
+    let password = password.as_ref();
+    let encoded_password = encoded_password.as_ref();
+    let pepper = pepper.as_ref();
+    check_password_length(password.as_bytes())?;
+    let peppered = pepper_password(password.as_bytes(), pepper)?;
+    django_auth_bytes(peppered.as_ref(), encoded_password)
+}
+
+/// `HMAC-SHA256(pepper, password)`.
+fn pepper_password(password: &[u8], pepper: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    if pepper.is_empty() {
+        return Err(Error::InvalidPassword(
+            "pepper should not be empty".to_owned(),
+        ));
+    }
+    let mut mac = Hmac::<Sha256>::new_from_slice(pepper).expect("HMAC accepts any key length");
+    mac.update(password);
+    Ok(Zeroizing::new(mac.finalize().into_bytes().into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{django_auth, django_encode_password};
+
+    #[test]
+    fn test_pepper() {
+        let encoded_password =
+            django_encode_password_with_pepper("hello", "seasalt", 1000, b"serversecret").unwrap();
+        // pbkdf2(hmac.new(b"serversecret", b"hello", "sha256").digest())
+        assert_eq!(
+            encoded_password,
+            "pbkdf2_sha256$1000$seasalt$lgDOM2gOduYW93pKY8Z1WbwGGfb9SOKqza4UlA9jw/Q="
+        );
+
+        assert!(django_auth_with_pepper("hello", &encoded_password, b"serversecret").unwrap());
+        assert!(!django_auth_with_pepper("world", &encoded_password, b"serversecret").unwrap());
+        assert!(!django_auth_with_pepper("hello", &encoded_password, b"otherpepper").unwrap());
+        assert!(!django_auth("hello", &encoded_password).unwrap());
+
+        // a hash encoded without pepper doesn't verify with one
+        let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+        assert!(!django_auth_with_pepper("hello", &encoded_password, b"serversecret").unwrap());
+        assert!(!django_auth_with_pepper("hello", "!", b"serversecret").unwrap());
+    }
+
+    #[test]
+    fn test_pepper_errors() {
+        let res = django_encode_password_with_pepper("hello", "seasalt", 1000, b"");
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_auth_with_pepper("hello", "pbkdf2_sha256$1000$seasalt$hash", b"");
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+
+        // like django_encode_password, even though the HMAC isn't empty
+        let res = django_encode_password_with_pepper("", "seasalt", 1000, b"serversecret");
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+        let res = django_encode_password_with_pepper("hello", "", 1000, b"serversecret");
+        assert!(matches!(res, Err(Error::InvalidSalt(_))));
+
+        let long = "a".repeat(crate::max_password_length() + 1);
+        let res = django_auth_with_pepper(&long, "pbkdf2_sha256$1000$seasalt$hash", b"pepper");
+        assert!(matches!(res, Err(Error::InvalidPassword(_))));
+    }
+}
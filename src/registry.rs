@@ -0,0 +1,282 @@
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::hashers::*;
+use crate::{check_password_length, identify_hasher, is_password_usable, Algorithm, Error, Result};
+
+/// A set of hashers, mapping algorithm names to [Hasher]s, like Django's
+/// `PASSWORD_HASHERS` setting.
+///
+/// [HasherRegistry::default] registers all the built-in hashers, use
+/// [HasherRegistry::new] and [HasherRegistry::register] to accept only some
+/// algorithms (or custom ones).
+///
+/// The first registered hasher is the preferred one, new passwords should be
+/// encoded with it, see [HasherRegistry::must_update].
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let mut registry = HasherRegistry::new();
+/// registry.register(Pbkdf2Sha256Hasher::default());
+///
+/// // pbkdf2_sha1 is not accepted by this registry (nor compiled in without
+/// // the `legacy-sha1` feature)
+/// let res = registry.verify(
+///     "hello",
+///     "pbkdf2_sha1$180000$btQDcwXF2RoK6Q$6JOEcGwOZsGrn0ysuB/Sp20EBx8=",
+/// );
+/// assert!(matches!(
+///     res,
+///     Err(Error::UnsupportedAlgorithm(_) | Error::FeatureNotEnabled(_))
+/// ));
+/// ```
+///
+pub struct HasherRegistry {
+    hashers: Vec<Box<dyn Hasher>>,
+}
+
+impl HasherRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self {
+            hashers: Vec::new(),
+        }
+    }
+
+    /// Register `hasher`, replacing the registered hasher of the same
+    /// algorithm if any.
+    pub fn register(&mut self, hasher: impl Hasher + 'static) {
+        let hasher: Box<dyn Hasher> = Box::new(hasher);
+        match self
+            .hashers
+            .iter_mut()
+            .find(|h| h.algorithm() == hasher.algorithm())
+        {
+            Some(registered) => *registered = hasher,
+            None => self.hashers.push(hasher),
+        }
+    }
+
+    /// Return the hasher registered for `algorithm`.
+    pub fn get(&self, algorithm: &str) -> Option<&dyn Hasher> {
+        self.hashers
+            .iter()
+            .find(|h| h.algorithm() == algorithm)
+            .map(|h| h.as_ref())
+    }
+
+    /// Verify `password` against `encoded_password` with the hasher
+    /// registered for its algorithm, see [crate::django_auth].
+    ///
+    /// Unusable passwords (see [crate::is_password_usable]) never match, and
+    /// passwords longer than [crate::max_password_length] are rejected.
+    /// Surrounding ASCII whitespace (e.g. a trailing newline from a database
+    /// export) is ignored in `encoded_password`.
+    pub fn verify(&self, password: &str, encoded_password: &str) -> Result<bool> {
+        check_password_length(password.as_bytes())?;
+        let encoded_password = encoded_password.trim_ascii();
+        if !is_password_usable(encoded_password) {
+            return Ok(false);
+        }
+        self.hasher_for(encoded_password)?
+            .verify(password, encoded_password)
+    }
+
+    /// Same as [HasherRegistry::verify] for a password which may not be valid
+    /// UTF-8, see [Hasher::verify_bytes].
+    pub fn verify_bytes(&self, password: &[u8], encoded_password: &str) -> Result<bool> {
+        check_password_length(password)?;
+        let encoded_password = encoded_password.trim_ascii();
+        if !is_password_usable(encoded_password) {
+            return Ok(false);
+        }
+        self.hasher_for(encoded_password)?
+            .verify_bytes(password, encoded_password)
+    }
+
+    /// Whether `encoded_password` should be re-encoded with the preferred
+    /// (first registered) hasher, i.e. when it uses another algorithm, or
+    /// weaker parameters than the preferred hasher's (see
+    /// [Hasher::must_update]).
+    ///
+    /// This is Django's `check_password` upgrade logic, e.g. registering
+    /// [Argon2Hasher] before [Pbkdf2Sha256Hasher] migrates pbkdf2 passwords to
+    /// argon2 as users log in. Unusable passwords never need an update.
+    pub fn must_update(&self, encoded_password: &str) -> Result<bool> {
+        let encoded_password = encoded_password.trim_ascii();
+        if !is_password_usable(encoded_password) {
+            return Ok(false);
+        }
+        let hasher = self.hasher_for(encoded_password)?;
+        if self.hashers[0].algorithm() != hasher.algorithm() {
+            return Ok(true);
+        }
+        hasher.must_update(encoded_password)
+    }
+
+    /// Return the hasher registered for the algorithm of `encoded_password`.
+    ///
+    /// [Error::FeatureNotEnabled] tells which feature to enable when the
+    /// algorithm isn't registered because it isn't compiled in.
+    pub(crate) fn hasher_for(&self, encoded_password: &str) -> Result<&dyn Hasher> {
+        let algorithm = identify_hasher(encoded_password)?;
+        self.get(algorithm)
+            .ok_or_else(|| match algorithm.parse::<Algorithm>() {
+                Ok(algorithm) if !algorithm.is_enabled() => algorithm.feature_not_enabled(),
+                _ => Error::UnsupportedAlgorithm(algorithm.to_owned()),
+            })
+    }
+}
+
+impl Default for HasherRegistry {
+    /// Create a registry with all the built-in hashers which are compiled in
+    /// (see the crate features), using their default parameters.
+    fn default() -> Self {
+        let mut registry = Self::new();
+        registry.register(Pbkdf2Sha256Hasher::default());
+        #[cfg(feature = "legacy-sha1")]
+        registry.register(Pbkdf2Sha1Hasher::default());
+        registry.register(Pbkdf2Sha512Hasher::default());
+        registry.register(Pbkdf2Sha256HexHasher::default());
+        registry.register(Pbkdf2Sha256B64SaltHasher::default());
+        registry.register(wrapped::Pbkdf2WrappedHasher);
+        #[cfg(feature = "argon2")]
+        registry.register(Argon2Hasher::default());
+        #[cfg(feature = "bcrypt")]
+        registry.register(BcryptSha256Hasher::default());
+        #[cfg(feature = "bcrypt")]
+        registry.register(BcryptHasher::default());
+        #[cfg(feature = "scrypt")]
+        registry.register(ScryptHasher::default());
+        #[cfg(feature = "insecure-hashers")]
+        registry.register(UnsaltedMd5Hasher);
+        #[cfg(feature = "insecure-hashers")]
+        registry.register(UnsaltedSha1Hasher);
+        #[cfg(feature = "insecure-hashers")]
+        registry.register(Sha1Hasher);
+        #[cfg(feature = "insecure-hashers")]
+        registry.register(Md5Hasher);
+        #[cfg(feature = "insecure-hashers")]
+        registry.register(CryptHasher);
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stores passwords reversed, for testing only.
+    struct ReverseHasher;
+
+    impl Hasher for ReverseHasher {
+        fn algorithm(&self) -> &str {
+            "reverse"
+        }
+
+        fn encode(&self, password: &str, salt: &str) -> Result<String> {
+            let reversed: String = password.chars().rev().collect();
+            Ok(format!("reverse${salt}${reversed}"))
+        }
+
+        fn verify(&self, password: &str, encoded: &str) -> Result<bool> {
+            let (_, salt) = encoded.split_once('$').unwrap();
+            let (salt, _) = salt.split_once('$').unwrap();
+            Ok(self.encode(password, salt)? == encoded)
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let registry = HasherRegistry::default();
+        assert_eq!(
+            registry.get("pbkdf2_sha256").unwrap().algorithm(),
+            "pbkdf2_sha256"
+        );
+        assert!(registry.get("reverse").is_none());
+        assert!(registry
+            .verify(
+                "hello",
+                "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+            )
+            .unwrap());
+
+        let mut registry = HasherRegistry::new();
+        registry.register(ReverseHasher);
+        let encoded = registry
+            .get("reverse")
+            .unwrap()
+            .encode("hello", "salt")
+            .unwrap();
+        assert_eq!(encoded, "reverse$salt$olleh");
+        assert!(registry.verify("hello", &encoded).unwrap());
+        assert!(!registry.verify("world", &encoded).unwrap());
+        assert!(registry.verify_bytes(b"hello", &encoded).unwrap());
+        assert!(matches!(
+            registry.verify_bytes(b"\xffhello", &encoded),
+            Err(Error::InvalidPassword(_))
+        ));
+
+        let res = registry.verify(
+            "hello",
+            "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+        );
+        assert!(matches!(res, Err(Error::UnsupportedAlgorithm(_))));
+    }
+
+    #[test]
+    fn test_registry_must_update() {
+        let mut registry = HasherRegistry::new();
+        registry.register(Pbkdf2Sha256Hasher { iterations: 1000 });
+        registry.register(ReverseHasher);
+
+        // iteration bump
+        let encoded = Pbkdf2Sha256Hasher { iterations: 1000 }
+            .encode("hello", "seasalt")
+            .unwrap();
+        assert!(!registry.must_update(&encoded).unwrap());
+        let encoded = Pbkdf2Sha256Hasher { iterations: 999 }
+            .encode("hello", "seasalt")
+            .unwrap();
+        assert!(registry.must_update(&encoded).unwrap());
+
+        // algorithm change
+        assert!(registry.must_update("reverse$salt$olleh").unwrap());
+
+        assert!(!registry.must_update("!unusable").unwrap());
+        assert!(matches!(
+            registry.must_update("foo$seasalt$hash"),
+            Err(Error::UnsupportedAlgorithm(_))
+        ));
+
+        #[cfg(feature = "argon2")]
+        {
+            let mut registry = HasherRegistry::new();
+            registry.register(Argon2Hasher::default());
+            registry.register(Pbkdf2Sha256Hasher::default());
+            let encoded = Pbkdf2Sha256Hasher { iterations: 1000 }
+                .encode("hello", "seasalt")
+                .unwrap();
+            assert!(registry.must_update(&encoded).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_registry_register_replaces() {
+        let mut registry = HasherRegistry::new();
+        registry.register(Pbkdf2Sha256Hasher::default());
+        registry.register(Pbkdf2Sha256Hasher { iterations: 1000 });
+        assert_eq!(registry.hashers.len(), 1);
+
+        let encoded = registry
+            .get("pbkdf2_sha256")
+            .unwrap()
+            .encode("hello", "seasalt")
+            .unwrap();
+        assert!(encoded.starts_with("pbkdf2_sha256$1000$"));
+    }
+}
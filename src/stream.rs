@@ -0,0 +1,203 @@
+use alloc::vec::Vec;
+
+use sha2::{Digest, Sha256};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{django_auth_bytes, max_password_length, password_too_long, Result};
+
+// HMAC (thus pbkdf2) replaces the keys longer than the hash block size by
+// their digest
+const SHA256_BLOCK_SIZE: usize = 64;
+
+/// Verify a password received in chunks, e.g. by a proxy reading it from the
+/// network, against an encoded password, like [crate::django_auth_bytes] does
+/// with the whole password.
+///
+/// pbkdf2_sha256 passwords longer than 64 bytes are hashed by HMAC before
+/// use, so their chunks are hashed as they come instead of being buffered.
+/// Otherwise, the chunks are buffered (in memory which is zeroed on drop)
+/// until [StreamingVerifier::finalize]. Passwords longer than
+/// [crate::max_password_length] are rejected, without buffering more than
+/// that.
+///
+/// The one-shot [crate::django_auth] is simpler when the password is already
+/// in memory.
+///
+/// # Usage
+///
+/// ```rust
+/// use django_auth::*;
+///
+/// let mut verifier = StreamingVerifier::new(
+///     "pbkdf2_sha256$180000$btQDcwXF2RoK6Q$D4cC7bgbaIZGHsTdw9TYhRfuLfLGbsZlI4Rp802e7kU=",
+/// );
+/// verifier.update(b"hel");
+/// verifier.update(b"lo");
+/// assert!(verifier.finalize().expect("verify error"));
+/// ```
+///
+pub struct StreamingVerifier<'a> {
+    encoded_password: &'a str,
+    len: usize,
+    state: State,
+}
+
+enum State {
+    /// The password so far.
+    Buffered(Zeroizing<Vec<u8>>),
+    /// The SHA256 digest of a long pbkdf2_sha256 password so far.
+    Prehashed(Sha256),
+    /// The password is longer than the maximum length at the time.
+    TooLong(usize),
+}
+
+impl<'a> StreamingVerifier<'a> {
+    /// Create a verifier of a password against `encoded_password`.
+    pub fn new(encoded_password: &'a str) -> Self {
+        Self {
+            encoded_password: encoded_password.trim_ascii(),
+            len: 0,
+            state: State::Buffered(Zeroizing::new(Vec::with_capacity(SHA256_BLOCK_SIZE))),
+        }
+    }
+
+    /// Append `chunk` to the password.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.len = self.len.saturating_add(chunk.len());
+        let max = max_password_length();
+        if self.len > max {
+            self.state = State::TooLong(max);
+        }
+        let prehash =
+            self.len > SHA256_BLOCK_SIZE && self.encoded_password.starts_with("pbkdf2_sha256$");
+
+        match &mut self.state {
+            State::Buffered(buffer) if prehash => {
+                let mut hasher = Sha256::new();
+                hasher.update(buffer.as_slice());
+                hasher.update(chunk);
+                self.state = State::Prehashed(hasher);
+            }
+            State::Buffered(buffer) => push(buffer, chunk),
+            State::Prehashed(hasher) => hasher.update(chunk),
+            State::TooLong(_) => {}
+        }
+    }
+
+    /// Verify the password, see [crate::django_auth_bytes].
+    pub fn finalize(self) -> Result<bool> {
+        match self.state {
+            State::Buffered(buffer) => django_auth_bytes(&buffer, self.encoded_password),
+            State::Prehashed(hasher) => {
+                let mut digest = hasher.finalize();
+                let res = django_auth_bytes(digest.as_slice(), self.encoded_password);
+                digest.zeroize();
+                res
+            }
+            State::TooLong(max) => Err(password_too_long(max)),
+        }
+    }
+}
+
+/// Append `chunk` to `buffer`, growing it without leaving a copy of its
+/// content in the freed memory.
+fn push(buffer: &mut Zeroizing<Vec<u8>>, chunk: &[u8]) {
+    let len = buffer.len() + chunk.len();
+    if len > buffer.capacity() {
+        let mut grown = Zeroizing::new(Vec::with_capacity(len.next_power_of_two()));
+        grown.extend_from_slice(buffer);
+        // the previous buffer is zeroed on drop
+        *buffer = grown;
+    }
+    buffer.extend_from_slice(chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{django_auth_bytes, django_encode_password_bytes, Error};
+
+    fn verify_chunks(chunks: &[&[u8]], encoded_password: &str) -> Result<bool> {
+        let mut verifier = StreamingVerifier::new(encoded_password);
+        for chunk in chunks {
+            verifier.update(chunk);
+        }
+        verifier.finalize()
+    }
+
+    #[test]
+    fn test_streaming_verifier() {
+        for len in [1, 63, 64, 65, 200, 4096] {
+            // no NUL bytes, HMAC pads short keys with them
+            let password: Vec<u8> = (0..len).map(|i| (i % 255 + 1) as u8).collect();
+            let encoded_password =
+                django_encode_password_bytes(&password, "seasalt", 1000).unwrap();
+
+            for chunk_size in [1, 7, 64, len] {
+                let chunks: Vec<&[u8]> = password.chunks(chunk_size).collect();
+                assert!(verify_chunks(&chunks, &encoded_password).unwrap(), "{len}");
+            }
+
+            let mut other = password.clone();
+            other[len - 1] ^= 1;
+            assert!(
+                !verify_chunks(&[&other], &encoded_password).unwrap(),
+                "{len}"
+            );
+            assert!(!verify_chunks(&[&password[1..]], &encoded_password).unwrap());
+        }
+
+        // the pbkdf2_sha256 prehash is what HMAC does
+        let password = [b'a'; 100];
+        let encoded_password = django_encode_password_bytes(password, "seasalt", 1000).unwrap();
+        assert!(django_auth_bytes(Sha256::digest(password), &encoded_password).unwrap());
+
+        // without any chunk
+        assert!(!verify_chunks(&[], &encoded_password).unwrap());
+        assert!(matches!(
+            verify_chunks(&[], "pbkdf2_sha256$1000$seasalt$"),
+            Err(Error::InvalidEncodedPassword(_))
+        ));
+        assert!(!verify_chunks(&[&password], "!").unwrap());
+        // the prehash is still detected with surrounding whitespace
+        let padded = format!(" {encoded_password}\n");
+        assert!(verify_chunks(&[&password], &padded).unwrap());
+    }
+
+    #[cfg(feature = "insecure-hashers")]
+    #[test]
+    fn test_streaming_verifier_buffered() {
+        // "md5$seasalt$" + hashlib.md5(b"seasaltl\xc3\xa8tmein").hexdigest()
+        let encoded_password = "md5$seasalt$3f86d0d3d465b7b458c231bf3555c0e3";
+        let chunks: [&[u8]; 3] = [b"l\xc3", b"\xa8tm", b"ein"];
+        assert!(verify_chunks(&chunks, encoded_password).unwrap());
+        assert!(!verify_chunks(&chunks[1..], encoded_password).unwrap());
+    }
+
+    #[test]
+    fn test_streaming_verifier_too_long() {
+        let encoded_password = "pbkdf2_sha256$1000$seasalt$hash";
+        let chunk = [b'a'; 1024];
+        let mut verifier = StreamingVerifier::new(encoded_password);
+        for _ in 0..=max_password_length() / chunk.len() {
+            verifier.update(&chunk);
+        }
+        assert!(matches!(verifier.state, State::TooLong(_)));
+        assert!(matches!(
+            verifier.finalize(),
+            Err(Error::InvalidPassword(_))
+        ));
+    }
+
+    #[test]
+    fn test_push() {
+        let mut buffer = Zeroizing::new(Vec::with_capacity(4));
+        push(&mut buffer, b"abc");
+        assert_eq!(buffer.capacity(), 4);
+        push(&mut buffer, b"defgh");
+        assert_eq!(buffer.as_slice(), b"abcdefgh");
+        assert!(buffer.capacity() >= 8);
+        push(&mut buffer, b"");
+        assert_eq!(buffer.as_slice(), b"abcdefgh");
+    }
+}
@@ -0,0 +1,17 @@
+//! JS bindings, built with the `wasm` feature.
+//!
+//! Errors are thrown as JS `Error`s carrying the message of [crate::Error].
+
+use wasm_bindgen::prelude::*;
+
+/// Verify `password` against `encoded_password`, see [crate::django_auth].
+#[wasm_bindgen(js_name = djangoAuth)]
+pub fn django_auth(password: &str, encoded_password: &str) -> Result<bool, JsError> {
+    Ok(crate::django_auth(password, encoded_password)?)
+}
+
+/// Encode `password` with a random salt, see [crate::make_password].
+#[wasm_bindgen(js_name = makePassword)]
+pub fn make_password(password: &str) -> Result<String, JsError> {
+    Ok(crate::make_password(password)?)
+}
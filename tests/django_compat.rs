@@ -0,0 +1,167 @@
+//! Cross-checks the encoded passwords against a Python Django installation,
+//! with the `pyo3-compat-tests` feature.
+//!
+//! The Python interpreter is `python3`, or `$DJANGO_AUTH_PYTHON`, and must be
+//! able to `import django`:
+//!
+//! ```sh
+//! pip install django
+//! cargo test --features pyo3-compat-tests --test django_compat
+//! ```
+//!
+//! The test is skipped (with a message on stderr) when Django can't be
+//! imported, set `DJANGO_AUTH_REQUIRE_DJANGO=1` to make it fail instead.
+
+#![cfg(feature = "pyo3-compat-tests")]
+
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use django_auth::*;
+
+/// Read `[algorithm, password, salt, iterations]` JSON lines on stdin and
+/// write the matching Django encoded passwords on stdout, one per line.
+const ENCODE_SCRIPT: &str = r#"
+import json, sys
+from django.conf import settings
+settings.configure()
+from django.contrib.auth.hashers import PBKDF2PasswordHasher, PBKDF2SHA1PasswordHasher
+hashers = {h.algorithm: h() for h in (PBKDF2PasswordHasher, PBKDF2SHA1PasswordHasher)}
+for line in sys.stdin:
+    algorithm, password, salt, iterations = json.loads(line)
+    print(hashers[algorithm].encode(password, salt, iterations))
+"#;
+
+/// Same as [ENCODE_SCRIPT] for argon2, with
+/// `[password, salt, memory_cost, time_cost, parallelism]` lines.
+const ARGON2_SCRIPT: &str = r#"
+import json, sys
+from django.conf import settings
+settings.configure()
+from django.contrib.auth.hashers import Argon2PasswordHasher
+for line in sys.stdin:
+    password, salt, memory_cost, time_cost, parallelism = json.loads(line)
+    hasher = Argon2PasswordHasher()
+    hasher.memory_cost, hasher.time_cost, hasher.parallelism = memory_cost, time_cost, parallelism
+    print(hasher.encode(password, salt))
+"#;
+
+fn python() -> String {
+    env::var("DJANGO_AUTH_PYTHON").unwrap_or_else(|_| "python3".to_owned())
+}
+
+fn django_available(modules: &str) -> bool {
+    let available = Command::new(python())
+        .args(["-c", &format!("import {modules}")])
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success());
+    if !available && env::var_os("DJANGO_AUTH_REQUIRE_DJANGO").is_some() {
+        panic!("{} can't import {modules}", python());
+    }
+    available
+}
+
+/// Encode the JSON `cases` with Django, running `script`.
+fn django_encode(script: &str, cases: impl Iterator<Item = String>) -> Vec<String> {
+    let mut child = Command::new(python())
+        .args(["-c", script])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to run python");
+
+    let mut stdin = child.stdin.take().unwrap();
+    for case in cases {
+        writeln!(stdin, "{case}").unwrap();
+    }
+    drop(stdin);
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "django failed");
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn test_django_encode_password_compat() {
+    if !django_available("django") {
+        eprintln!("django can't be imported with {}, skipped", python());
+        return;
+    }
+
+    let cases = [
+        ("pbkdf2_sha256", "hello", "seasalt", 1000),
+        ("pbkdf2_sha256", "hello", "btQDcwXF2RoK6Q", 180000),
+        ("pbkdf2_sha256", "lètmein", "ab", 1),
+        ("pbkdf2_sha256", "", "seasalt", 1000),
+        (
+            "pbkdf2_sha256",
+            "p4ssw0rd with spaces",
+            "0123456789abcdefghij",
+            320000,
+        ),
+        ("pbkdf2_sha256", "🦀", "seasalt", 600000),
+        #[cfg(feature = "legacy-sha1")]
+        ("pbkdf2_sha1", "hello", "btQDcwXF2RoK6Q", 180000),
+        #[cfg(feature = "legacy-sha1")]
+        ("pbkdf2_sha1", "lètmein", "seasalt", 1000),
+    ];
+
+    let expected = django_encode(
+        ENCODE_SCRIPT,
+        cases
+            .iter()
+            .map(|case| serde_json::to_string(case).unwrap()),
+    );
+    assert_eq!(expected.len(), cases.len());
+    for (&(algorithm, password, salt, iterations), expected) in cases.iter().zip(&expected) {
+        let encoded = match algorithm {
+            "pbkdf2_sha256" => django_encode_password_allow_empty(password, salt, iterations),
+            #[cfg(feature = "legacy-sha1")]
+            "pbkdf2_sha1" => django_encode_password_pbkdf2_sha1(password, salt, iterations),
+            _ => unreachable!(),
+        }
+        .unwrap();
+        assert_eq!(&encoded, expected, "{algorithm} {password:?}");
+        if !password.is_empty() {
+            assert!(django_auth(password, expected).unwrap());
+        }
+    }
+}
+
+#[cfg(feature = "argon2")]
+#[test]
+fn test_argon2_encode_compat() {
+    if !django_available("django, argon2") {
+        eprintln!(
+            "django or argon2-cffi can't be imported with {}, skipped",
+            python()
+        );
+        return;
+    }
+
+    let cases = [
+        ("hello", "btQDcwXF2RoK6Q", 8, 1, 1),
+        ("lètmein", "abcdefghijk", 64, 2, 2),
+        ("secret", "cN5tLa6IcEE2", 102400, 2, 8),
+    ];
+
+    let expected = django_encode(
+        ARGON2_SCRIPT,
+        cases
+            .iter()
+            .map(|case| serde_json::to_string(case).unwrap()),
+    );
+    assert_eq!(expected.len(), cases.len());
+    for (&(password, salt, memory_kib, time_cost, parallelism), expected) in
+        cases.iter().zip(&expected)
+    {
+        let encoded = argon2_encode(password, salt, memory_kib, time_cost, parallelism).unwrap();
+        assert_eq!(&encoded, expected, "{password:?}");
+    }
+}
@@ -0,0 +1,46 @@
+//! Checks the log events emitted with the `log` feature.
+
+#![cfg(feature = "log")]
+
+use std::sync::Mutex;
+
+use django_auth::*;
+use log::{Level, Log, Metadata, Record};
+
+struct Collector(Mutex<Vec<(Level, String)>>);
+
+impl Log for Collector {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        let event = (record.level(), record.args().to_string());
+        self.0.lock().unwrap().push(event);
+    }
+
+    fn flush(&self) {}
+}
+
+static COLLECTOR: Collector = Collector(Mutex::new(Vec::new()));
+
+#[test]
+fn test_log_events() {
+    log::set_logger(&COLLECTOR).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let encoded_password = django_encode_password("hello", "seasalt", 1000).unwrap();
+    assert!(django_auth("hello", &encoded_password).unwrap());
+    assert!(needs_update(&encoded_password, 2000).unwrap());
+    assert!(django_auth("hello", "pbkdf2_sha256$1000$seasalt$not base64!").is_err());
+
+    let events = COLLECTOR.0.lock().unwrap();
+    let levels: Vec<Level> = events.iter().map(|(level, _)| *level).collect();
+    assert_eq!(levels, [Level::Debug, Level::Warn, Level::Error]);
+
+    let (_, hash) = encoded_password.rsplit_once('$').unwrap();
+    for (_, message) in events.iter() {
+        assert!(!message.contains("hello"), "{message}");
+        assert!(!message.contains(hash), "{message}");
+    }
+}
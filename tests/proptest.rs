@@ -0,0 +1,52 @@
+use django_auth::*;
+use proptest::prelude::*;
+
+const SALT: &str = "[a-zA-Z0-9]{1,32}";
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn encode_verify_roundtrip(password in ".{1,64}", salt in SALT, iterations in 1u32..50) {
+        let encoded_password = django_encode_password(&password, &salt, iterations).unwrap();
+
+        let prefix = format!("pbkdf2_sha256${iterations}${salt}$");
+        prop_assert!(encoded_password.starts_with(&prefix));
+        prop_assert!(django_auth(&password, &encoded_password).unwrap());
+    }
+
+    #[test]
+    fn verify_other_password(
+        password in ".{1,64}",
+        other in ".{0,64}",
+        salt in SALT,
+        iterations in 1u32..50,
+    ) {
+        prop_assume!(password != other);
+        let encoded_password = django_encode_password(&password, &salt, iterations).unwrap();
+
+        prop_assert!(!django_auth(&other, &encoded_password).unwrap());
+    }
+
+    #[test]
+    fn encode_verify_bytes_roundtrip(
+        password in prop::collection::vec(any::<u8>(), 1..64),
+        other in prop::collection::vec(any::<u8>(), 0..64),
+        salt in SALT,
+        iterations in 1u32..50,
+    ) {
+        let encoded_password = django_encode_password_bytes(&password, &salt, iterations).unwrap();
+
+        prop_assert!(django_auth_bytes(&password, &encoded_password).unwrap());
+        prop_assert_eq!(django_auth_bytes(&other, &encoded_password).unwrap(), password == other);
+    }
+
+    #[cfg(feature = "legacy-sha1")]
+    #[test]
+    fn encode_verify_roundtrip_pbkdf2_sha1(password in ".{1,64}", salt in SALT, iterations in 1u32..50) {
+        let encoded_password =
+            django_encode_password_pbkdf2_sha1(&password, &salt, iterations).unwrap();
+
+        prop_assert!(django_auth(&password, &encoded_password).unwrap());
+    }
+}